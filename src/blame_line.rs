@@ -0,0 +1,75 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+/// A line of a file, along with the commit that last changed it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlameLine {
+    sha: String,
+    line_number: u32,
+    author: String,
+    author_email: Option<String>,
+    content: String,
+}
+
+impl BlameLine {
+    /// The SHA of the commit that last changed the line
+    pub fn sha(&self) -> &str {
+        self.sha.as_str()
+    }
+    /// The line's number in the file, starting from 1
+    pub fn line_number(&self) -> u32 {
+        self.line_number
+    }
+    /// The name of the author of the commit that last changed the line
+    pub fn author(&self) -> &str {
+        self.author.as_str()
+    }
+    /// The email address of the author of the commit that last changed the line, without the
+    /// angle brackets Git puts around it
+    pub fn author_email(&self) -> &Option<String> {
+        &self.author_email
+    }
+    pub fn content(&self) -> &str {
+        self.content.as_str()
+    }
+
+    /// Parses the output of `git blame --line-porcelain`, where each line of the file has a
+    /// header line of `<sha> <original-line> <final-line> [<count>]`, followed by lines of
+    /// information about the commit, and finally the line itself, prefixed with a tab.
+    pub(crate) fn from_line_porcelain(output: &str) -> Vec<BlameLine> {
+        let mut lines = vec![];
+        let mut header: Option<(String, u32)> = None;
+        let mut author = String::new();
+        let mut author_email = None;
+        for line in output.lines() {
+            if let Some(content) = line.strip_prefix('\t') {
+                if let Some((sha, line_number)) = header.take() {
+                    lines.push(BlameLine {
+                        sha,
+                        line_number,
+                        author: std::mem::take(&mut author),
+                        author_email: author_email.take(),
+                        content: content.to_owned(),
+                    });
+                }
+            } else if header.is_none() {
+                let mut fields = line.split(' ');
+                if let (Some(sha), Some(_), Some(line_number)) =
+                    (fields.next(), fields.next(), fields.next())
+                {
+                    if let Ok(line_number) = line_number.parse() {
+                        header = Some((sha.to_owned(), line_number));
+                    }
+                }
+            } else if let Some(name) = line.strip_prefix("author ") {
+                author = name.to_owned();
+            } else if let Some(email) = line.strip_prefix("author-mail ") {
+                let email = email.trim_start_matches('<').trim_end_matches('>');
+                author_email = Some(email.to_owned());
+            }
+        }
+        lines
+    }
+}
@@ -0,0 +1,41 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+use crate::status_entry::StatusEntry;
+use std::path::PathBuf;
+
+/// How a file was changed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    /// The file was renamed from this path
+    Renamed(PathBuf),
+    /// The file was copied from this path
+    Copied(PathBuf),
+    /// The file changed type, e.g. from a regular file to a symbolic link
+    TypeChanged,
+    /// The file has unresolved conflicts
+    Unmerged,
+}
+
+impl ChangeKind {
+    /// Returns the kind of change a status entry describes, or `None` if its status isn't one
+    /// this type models, e.g. `X` for an unknown change.
+    pub(crate) fn from_status_entry(entry: &StatusEntry) -> Option<ChangeKind> {
+        let original_path = || entry.original_path().clone().unwrap_or_default();
+        match entry.status() {
+            'A' => Some(ChangeKind::Added),
+            'M' => Some(ChangeKind::Modified),
+            'D' => Some(ChangeKind::Deleted),
+            'R' => Some(ChangeKind::Renamed(original_path())),
+            'C' => Some(ChangeKind::Copied(original_path())),
+            'T' => Some(ChangeKind::TypeChanged),
+            'U' => Some(ChangeKind::Unmerged),
+            _ => None,
+        }
+    }
+}
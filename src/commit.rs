@@ -0,0 +1,55 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+/// A commit, as reported by `git log`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Commit {
+    sha: String,
+    author_name: String,
+    author_email: String,
+    author_date: String,
+    subject: String,
+}
+
+impl Commit {
+    /// The `--format` that produces the output `from_log` parses: fields separated by
+    /// unit separators and commits terminated by record separators, neither of which
+    /// can appear in the fields themselves.
+    pub(crate) const FORMAT: &'static str = "--format=%H%x1f%an%x1f%ae%x1f%aI%x1f%s%x1e";
+
+    pub fn sha(&self) -> &str {
+        self.sha.as_str()
+    }
+    pub fn author_name(&self) -> &str {
+        self.author_name.as_str()
+    }
+    pub fn author_email(&self) -> &str {
+        self.author_email.as_str()
+    }
+    /// The author date, in strict ISO 8601 format
+    pub fn author_date(&self) -> &str {
+        self.author_date.as_str()
+    }
+    pub fn subject(&self) -> &str {
+        self.subject.as_str()
+    }
+
+    /// Parses the output of `git log` run with `Commit::FORMAT`
+    pub(crate) fn from_log(output: &str) -> Vec<Commit> {
+        output
+            .split('\x1e')
+            .filter_map(|record| {
+                let mut fields = record.trim_start_matches('\n').split('\x1f');
+                Some(Commit {
+                    sha: fields.next().filter(|sha| !sha.is_empty())?.to_owned(),
+                    author_name: fields.next()?.to_owned(),
+                    author_email: fields.next()?.to_owned(),
+                    author_date: fields.next()?.to_owned(),
+                    subject: fields.next()?.to_owned(),
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,52 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+use std::path::PathBuf;
+
+/// A configuration value, along with where it was set
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigEntry {
+    key: String,
+    value: String,
+    origin: String,
+}
+
+impl ConfigEntry {
+    pub fn key(&self) -> &str {
+        self.key.as_str()
+    }
+    pub fn value(&self) -> &str {
+        self.value.as_str()
+    }
+    /// Where the value was set, as Git reports it, e.g. `file:.git/config` or `command line:`
+    pub fn origin(&self) -> &str {
+        self.origin.as_str()
+    }
+    /// The file the value was set in, if it was set in a file. Relative paths are relative to
+    /// the directory Git was run in.
+    pub fn origin_path(&self) -> Option<PathBuf> {
+        self.origin.strip_prefix("file:").map(PathBuf::from)
+    }
+
+    /// Parses the output of `git config --list --show-origin -z`, which alternates origins with
+    /// key/value records: `file:<path>\0<key>\n<value>\0`.
+    pub(crate) fn from_list_z(output: &str) -> Vec<ConfigEntry> {
+        let mut entries = vec![];
+        let mut fields = output.split('\0');
+        while let (Some(origin), Some(record)) = (fields.next(), fields.next()) {
+            let (key, value) = match record.split_once('\n') {
+                Some((key, value)) => (key, value),
+                // A key without a value, which Git treats as true
+                None => (record, ""),
+            };
+            entries.push(ConfigEntry {
+                key: key.to_owned(),
+                value: value.to_owned(),
+                origin: origin.to_owned(),
+            });
+        }
+        entries
+    }
+}
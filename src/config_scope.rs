@@ -0,0 +1,25 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+/// Which of Git's configuration files to use
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigScope {
+    /// The file for every user on the system (`--system`)
+    System,
+    /// The current user's file (`--global`)
+    Global,
+    /// The repository's own file (`--local`)
+    Local,
+}
+
+impl ConfigScope {
+    pub(crate) fn arg(&self) -> &'static str {
+        match self {
+            ConfigScope::System => "--system",
+            ConfigScope::Global => "--global",
+            ConfigScope::Local => "--local",
+        }
+    }
+}
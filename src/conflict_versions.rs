@@ -0,0 +1,49 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+/// The versions of a conflicted file that are being merged.
+/// A version is missing if the file didn't exist on that side, e.g. there's no base version
+/// when both sides added the file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConflictVersions {
+    base: Option<Vec<u8>>,
+    ours: Option<Vec<u8>>,
+    theirs: Option<Vec<u8>>,
+}
+
+impl ConflictVersions {
+    /// The version from the common ancestor (stage 1)
+    pub fn base(&self) -> &Option<Vec<u8>> {
+        &self.base
+    }
+    /// The version from the current branch (stage 2)
+    pub fn ours(&self) -> &Option<Vec<u8>> {
+        &self.ours
+    }
+    /// The version from the branch being merged (stage 3)
+    pub fn theirs(&self) -> &Option<Vec<u8>> {
+        &self.theirs
+    }
+
+    pub(crate) fn set_stage(&mut self, stage: u8, contents: Vec<u8>) {
+        match stage {
+            1 => self.base = Some(contents),
+            2 => self.ours = Some(contents),
+            3 => self.theirs = Some(contents),
+            _ => {}
+        }
+    }
+
+    /// Parses the stage numbers from the output of `git ls-files --unmerged -z`, where each
+    /// record is `<mode> <oid> <stage>\t<path>`.
+    pub(crate) fn stages_from_ls_files_z(output: &str) -> Vec<u8> {
+        output
+            .split('\0')
+            .filter_map(|record| record.split_once('\t'))
+            .filter_map(|(info, _)| info.rsplit(' ').next())
+            .filter_map(|stage| stage.parse().ok())
+            .collect()
+    }
+}
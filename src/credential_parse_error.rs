@@ -0,0 +1,28 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+use std::fmt;
+
+/// Why text couldn't be parsed as credentials
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CredentialParseError {
+    /// There weren't any attributes
+    Empty,
+    /// A line wasn't a `key=value` attribute. Holds the line.
+    MalformedLine(String),
+}
+
+impl fmt::Display for CredentialParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CredentialParseError::Empty => write!(f, "No credential attributes"),
+            CredentialParseError::MalformedLine(line) => {
+                write!(f, "Malformed credential attribute: {line:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CredentialParseError {}
@@ -0,0 +1,27 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+/// How a push may overwrite the remote branch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForceMode {
+    /// Only fast-forward pushes are allowed
+    No,
+    /// Overwrite the remote branch unconditionally (`--force`)
+    Force,
+    /// Overwrite the remote branch only if it is where our remote-tracking branch says it is
+    /// (`--force-with-lease`). This still fails if someone else has pushed to the branch since
+    /// we last fetched it.
+    WithLease,
+}
+
+impl ForceMode {
+    pub(crate) fn arg(&self) -> Option<&'static str> {
+        match self {
+            ForceMode::No => None,
+            ForceMode::Force => Some("--force"),
+            ForceMode::WithLease => Some("--force-with-lease"),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+/// Everything about a commit, as reported by `git log`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FullCommit {
+    sha: String,
+    tree: String,
+    parents: Vec<String>,
+    author_name: String,
+    author_email: String,
+    author_date: String,
+    committer_name: String,
+    committer_email: String,
+    committer_date: String,
+    subject: String,
+    body: String,
+}
+
+impl FullCommit {
+    /// The `--format` that produces the output `from_log` parses. The fields are separated by
+    /// unit separators, and the body comes last, since it can contain anything.
+    pub(crate) const FORMAT: &'static str =
+        "--format=%H%x1f%T%x1f%P%x1f%an%x1f%ae%x1f%aI%x1f%cn%x1f%ce%x1f%cI%x1f%s%x1f%b";
+
+    pub fn sha(&self) -> &str {
+        self.sha.as_str()
+    }
+    /// The OID of the commit's tree
+    pub fn tree(&self) -> &str {
+        self.tree.as_str()
+    }
+    /// The SHAs of the commit's parents, which is empty for a root commit
+    pub fn parents(&self) -> &Vec<String> {
+        &self.parents
+    }
+    pub fn author_name(&self) -> &str {
+        self.author_name.as_str()
+    }
+    pub fn author_email(&self) -> &str {
+        self.author_email.as_str()
+    }
+    /// The author date, in strict ISO 8601 format
+    pub fn author_date(&self) -> &str {
+        self.author_date.as_str()
+    }
+    pub fn committer_name(&self) -> &str {
+        self.committer_name.as_str()
+    }
+    pub fn committer_email(&self) -> &str {
+        self.committer_email.as_str()
+    }
+    /// The committer date, in strict ISO 8601 format
+    pub fn committer_date(&self) -> &str {
+        self.committer_date.as_str()
+    }
+    pub fn subject(&self) -> &str {
+        self.subject.as_str()
+    }
+    /// The rest of the message after the subject, without the blank line that separates them
+    pub fn body(&self) -> &str {
+        self.body.as_str()
+    }
+
+    /// Parses the output of `git log -1` run with `FullCommit::FORMAT`
+    pub(crate) fn from_log(output: &str) -> Option<FullCommit> {
+        let mut fields = output.splitn(11, '\x1f');
+        Some(FullCommit {
+            sha: fields.next()?.to_owned(),
+            tree: fields.next()?.to_owned(),
+            parents: fields
+                .next()?
+                .split_whitespace()
+                .map(|parent| parent.to_owned())
+                .collect(),
+            author_name: fields.next()?.to_owned(),
+            author_email: fields.next()?.to_owned(),
+            author_date: fields.next()?.to_owned(),
+            committer_name: fields.next()?.to_owned(),
+            committer_email: fields.next()?.to_owned(),
+            committer_date: fields.next()?.to_owned(),
+            subject: fields.next()?.to_owned(),
+            // Git adds a newline after the format
+            body: fields.next()?.trim_end_matches('\n').to_owned(),
+        })
+    }
+}
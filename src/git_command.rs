@@ -3,12 +3,17 @@
  * Created 2024-02-09
  */
 
+pub mod config;
 pub mod error;
-pub use error::Error;
+pub use config::{GitConfig, TraceHook};
+pub use error::{Error, ErrorKind};
 
-use std::io::Write;
+use std::io::{Read, Write};
 use std::path::PathBuf;
-use std::process::{Command, Output, Stdio};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 pub struct GitCommand {}
 
@@ -21,17 +26,50 @@ impl GitCommand {
         payload: Option<T>,
         current_dir: Option<&PathBuf>,
     ) -> Result<String, error::Error> {
-        let output = GitCommand::run_git_command(git_command, args, payload, current_dir)?;
+        GitCommand::git_command_with(&GitConfig::new(), git_command, args, payload, current_dir)
+    }
+
+    /// Runs a Git command with the given settings and returns its output if it succeeds.
+    /// Settings that aren't given are taken from the process-wide configuration.
+    pub fn git_command_with<T: ToString>(
+        config: &GitConfig,
+        git_command: &str,
+        args: Option<Vec<&str>>,
+        payload: Option<T>,
+        current_dir: Option<&PathBuf>,
+    ) -> Result<String, error::Error> {
+        let output =
+            GitCommand::run_git_command_with(config, git_command, args, payload, current_dir)?;
         if output.status.success() {
-            match std::str::from_utf8(&output.stdout) {
-                Ok(stdout) => Ok(stdout.to_owned()),
-                Err(error) => Err(error::Error::new(error.to_string())),
-            }
+            GitCommand::stdout(&output)
         } else {
-            match std::str::from_utf8(&output.stderr) {
-                Ok(stderr) => Err(error::Error::new(stderr.to_owned())),
-                Err(error) => Err(error::Error::new(error.to_string())),
-            }
+            Err(GitCommand::stderr_error(&output))
+        }
+    }
+
+    /// Sets the process-wide configuration used by subsequent commands
+    pub fn configure(config: GitConfig) {
+        GitConfig::set_default(config);
+    }
+
+    /// Returns the process-wide configuration
+    pub fn config() -> GitConfig {
+        GitConfig::default_config()
+    }
+
+    /// Returns the standard output of a finished Git command as a string
+    pub fn stdout(output: &Output) -> Result<String, error::Error> {
+        match std::str::from_utf8(&output.stdout) {
+            Ok(stdout) => Ok(stdout.to_owned()),
+            Err(error) => Err(error::Error::new(error.to_string())),
+        }
+    }
+
+    /// Returns an error made from the standard error of a finished Git command
+    pub fn stderr_error(output: &Output) -> error::Error {
+        match std::str::from_utf8(&output.stderr) {
+            Ok(stderr) => error::Error::new(stderr.to_owned()),
+            Err(error) => error::Error::new(error.to_string()),
         }
     }
 }
@@ -54,14 +92,21 @@ impl GitCommand {
         payload: Option<T>,
         current_dir: Option<&PathBuf>,
     ) -> Result<Output, error::Error> {
-        let mut command = Command::new("git");
-        if let Some(current_dir) = current_dir {
-            command.current_dir(current_dir);
-        }
-        command.args(Self::git_args(git_command, args));
+        GitCommand::run_git_command_with(&GitConfig::new(), git_command, args, payload, current_dir)
+    }
+
+    /// Runs a Git command with the given settings and returns its output.
+    /// Settings that aren't given are taken from the process-wide configuration.
+    pub fn run_git_command_with<T: ToString>(
+        config: &GitConfig,
+        git_command: &str,
+        args: Option<Vec<&str>>,
+        payload: Option<T>,
+        current_dir: Option<&PathBuf>,
+    ) -> Result<Output, error::Error> {
+        let config = config.or(&GitCommand::config());
+        let mut command = Self::command(&config, git_command, args, current_dir);
         command.stdin(Stdio::piped());
-        command.stdout(Stdio::piped());
-        command.stderr(Stdio::piped());
         match command.spawn() {
             Ok(mut child_process) => {
                 if let Some(payload) = payload {
@@ -75,12 +120,441 @@ impl GitCommand {
                         None => return Err(error::Error::new("Can't get stdin".to_owned())),
                     }
                 }
-                match child_process.wait_with_output() {
-                    Ok(output) => Ok(output),
-                    Err(error) => Err(error::Error::new(error.to_string())),
+                if config.stop_flag().is_some() || config.timeout().is_some() {
+                    Self::wait_or_stop(child_process, config.stop_flag(), config.timeout())
+                } else {
+                    match child_process.wait_with_output() {
+                        Ok(output) => Ok(output),
+                        Err(error) => Err(error::Error::new(error.to_string())),
+                    }
                 }
             }
+            Err(error) => Err(Self::spawn_error(error, config.program(), current_dir)),
+        }
+    }
+
+    /// Runs a Git command and copies its standard output to a writer as it's produced, rather
+    /// than collecting it in memory, and returns the number of bytes written if it succeeds.
+    pub fn git_command_to_writer(
+        git_command: &str,
+        args: Option<Vec<&str>>,
+        writer: &mut dyn Write,
+        current_dir: Option<&PathBuf>,
+    ) -> Result<u64, error::Error> {
+        GitCommand::git_command_to_writer_with(
+            &GitConfig::new(),
+            git_command,
+            args,
+            writer,
+            current_dir,
+        )
+    }
+
+    /// Runs a Git command with the given settings and copies its standard output to a writer as
+    /// it's produced, and returns the number of bytes written if it succeeds.
+    /// Settings that aren't given are taken from the process-wide configuration.
+    pub fn git_command_to_writer_with(
+        config: &GitConfig,
+        git_command: &str,
+        args: Option<Vec<&str>>,
+        writer: &mut dyn Write,
+        current_dir: Option<&PathBuf>,
+    ) -> Result<u64, error::Error> {
+        let config = config.or(&GitCommand::config());
+        let mut command = Self::command(&config, git_command, args, current_dir);
+        command.stdin(Stdio::null());
+        let mut child_process = match command.spawn() {
+            Ok(child_process) => child_process,
+            Err(error) => return Err(Self::spawn_error(error, config.program(), current_dir)),
+        };
+        // Standard error is read on a separate thread, so the child doesn't block on a full
+        // pipe while we copy its output
+        let stderr = child_process.stderr.take();
+        let stderr = thread::spawn(move || {
+            let mut buffer = vec![];
+            if let Some(mut stderr) = stderr {
+                let _ = stderr.read_to_end(&mut buffer);
+            }
+            buffer
+        });
+        // The child is waited for on another thread too, so it can be killed if it's stopped or
+        // times out while we're blocked copying its output
+        let stdout = child_process.stdout.take();
+        let status = thread::spawn(move || {
+            Self::wait_for_status(&mut child_process, config.stop_flag(), config.timeout())
+        });
+        let copied = match stdout {
+            Some(mut stdout) => std::io::copy(&mut stdout, writer),
+            None => Ok(0),
+        };
+        let status = match status.join() {
+            Ok(status) => status,
+            Err(_) => Err(error::Error::new("Error waiting for Git".to_owned())),
+        };
+        let stderr = stderr.join().unwrap_or_default();
+        let status = status?;
+        if !status.success() {
+            return Err(GitCommand::stderr_error(&Output {
+                status,
+                stdout: vec![],
+                stderr,
+            }));
+        }
+        match copied {
+            Ok(copied) => Ok(copied),
             Err(error) => Err(error::Error::new(error.to_string())),
         }
     }
+
+    /// Returns a command that runs Git with the given settings, with its standard output and
+    /// standard error piped
+    fn command(
+        config: &GitConfig,
+        git_command: &str,
+        args: Option<Vec<&str>>,
+        current_dir: Option<&PathBuf>,
+    ) -> Command {
+        let mut command = match config.program() {
+            Some(program) => Command::new(program),
+            None => Command::new("git"),
+        };
+        if let Some(current_dir) = current_dir {
+            command.current_dir(current_dir);
+        }
+        command.envs(config.env().iter().map(|(key, value)| (key, value)));
+        let git_args = Self::git_args(git_command, args);
+        if let Some(trace) = config.trace() {
+            trace(&git_args);
+        }
+        command.args(git_args);
+        command.stdout(Stdio::piped());
+        command.stderr(Stdio::piped());
+        command
+    }
+
+    /// Returns an error for a Git process that couldn't be started.
+    /// A missing working directory is reported the same way as a missing program, so that
+    /// has to be ruled out before blaming the program.
+    fn spawn_error(
+        error: std::io::Error,
+        program: &Option<PathBuf>,
+        current_dir: Option<&PathBuf>,
+    ) -> error::Error {
+        let dir_exists = current_dir.is_none_or(|dir| dir.is_dir());
+        if error.kind() == std::io::ErrorKind::NotFound && dir_exists {
+            let program = match program {
+                Some(program) => program.display().to_string(),
+                None => "git".to_owned(),
+            };
+            error::Error::with_kind(
+                ErrorKind::GitNotFound,
+                format!("Couldn't run {program}. Make sure Git is installed and on the PATH."),
+            )
+        } else {
+            error::Error::new(error.to_string())
+        }
+    }
+
+    /// Waits for a child process to finish, killing it if the stop flag is set or the timeout
+    /// passes first.
+    /// The output is read on separate threads, so the child doesn't block on a full pipe while
+    /// we poll.
+    fn wait_or_stop(
+        mut child_process: Child,
+        stop_flag: Option<&AtomicBool>,
+        timeout: Option<Duration>,
+    ) -> Result<Output, error::Error> {
+        fn read_all<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+            thread::spawn(move || {
+                let mut buffer = vec![];
+                if let Some(mut pipe) = pipe {
+                    let _ = pipe.read_to_end(&mut buffer);
+                }
+                buffer
+            })
+        }
+
+        drop(child_process.stdin.take());
+        let stdout = read_all(child_process.stdout.take());
+        let stderr = read_all(child_process.stderr.take());
+        let status = Self::wait_for_status(&mut child_process, stop_flag, timeout)?;
+        Ok(Output {
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        })
+    }
+
+    /// Waits for a child process to exit, polling so it can be killed if the stop flag is set or
+    /// the timeout passes first.
+    fn wait_for_status(
+        child_process: &mut Child,
+        stop_flag: Option<&AtomicBool>,
+        timeout: Option<Duration>,
+    ) -> Result<ExitStatus, error::Error> {
+        if stop_flag.is_none() && timeout.is_none() {
+            return match child_process.wait() {
+                Ok(status) => Ok(status),
+                Err(error) => Err(error::Error::new(error.to_string())),
+            };
+        }
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let stopped = if stop_flag.is_some_and(|stop_flag| stop_flag.load(Ordering::SeqCst)) {
+                Some((ErrorKind::Cancelled, "Cancelled".to_owned()))
+            } else if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                Some((ErrorKind::TimedOut, "Timed out".to_owned()))
+            } else {
+                None
+            };
+            if let Some((kind, message)) = stopped {
+                let _ = child_process.kill();
+                let _ = child_process.wait();
+                return Err(error::Error::with_kind(kind, message));
+            }
+            match child_process.try_wait() {
+                Ok(Some(status)) => return Ok(status),
+                Ok(None) => thread::sleep(Duration::from_millis(10)),
+                Err(error) => return Err(error::Error::new(error.to_string())),
+            }
+        }
+    }
+}
+
+/// Held by tests that change the process-wide configuration, so they don't overlap
+#[cfg(test)]
+static CONFIGURE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[test]
+fn configure() {
+    use std::sync::{Arc, Mutex};
+
+    // The configured program is the git on the path, so other tests running meanwhile
+    // aren't affected, and commands run with an empty PATH only work if it's used
+    let git = std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default())
+        .map(|dir| dir.join(if cfg!(windows) { "git.exe" } else { "git" }))
+        .find(|git| git.is_file())
+        .expect("Git isn't on the path");
+    let traced = Arc::new(Mutex::new(Vec::<String>::new()));
+    let recorder = traced.clone();
+    let no_path = GitConfig::new().with_env("PATH", "");
+
+    let _lock = CONFIGURE_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let previous = GitCommand::config();
+    GitCommand::configure(GitConfig::new().with_program(&git).with_trace(move |args| {
+        recorder
+            .lock()
+            .expect("Error recording trace")
+            .push(args.join(" "))
+    }));
+    let used_default =
+        GitCommand::git_command_with::<String>(&no_path, "--version", None, None, None);
+    let overridden = GitCommand::git_command_with::<String>(
+        &no_path.clone().with_program("git"),
+        "--version",
+        None,
+        None,
+        None,
+    );
+    GitCommand::configure(previous);
+
+    assert!(used_default
+        .expect("Error running configured git")
+        .starts_with("git version"));
+    assert!(overridden.is_err());
+    assert!(traced
+        .lock()
+        .expect("Error reading trace")
+        .contains(&"--version".to_owned()));
+}
+
+#[cfg(unix)]
+#[test]
+fn configure_program() {
+    use crate::test_repo::TestRepo;
+    use std::os::unix::fs::PermissionsExt;
+
+    // A stand-in for git that leaves a marker behind, so we can tell it was run
+    let dir = TestRepo::empty_dir();
+    let marker = dir.path().join("marker");
+    let shim = dir.path().join("git-shim");
+    std::fs::write(
+        &shim,
+        format!("#!/bin/sh\ntouch '{}'\nexec git \"$@\"\n", marker.display()),
+    )
+    .expect("Error writing shim");
+    std::fs::set_permissions(&shim, std::fs::Permissions::from_mode(0o755))
+        .expect("Error making shim executable");
+
+    // Merging with defaults the way the configured ones are, without changing the
+    // process-wide configuration other tests use
+    let defaults = GitConfig::new().with_program(&shim);
+    let merged = GitConfig::new().or(&defaults);
+    assert_eq!(merged.program(), &Some(shim.clone()));
+    let used_default =
+        GitCommand::git_command_with::<String>(&merged, "--version", None, None, None);
+    let overridden = GitCommand::git_command_with::<String>(
+        &GitConfig::new()
+            .with_program(dir.path().join("no-such-git"))
+            .or(&defaults),
+        "--version",
+        None,
+        None,
+        None,
+    );
+
+    assert!(used_default.is_ok());
+    assert!(marker.exists());
+    assert!(overridden.is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn timeout() {
+    use crate::test_repo::TestRepo;
+    use std::os::unix::fs::PermissionsExt;
+
+    // A stand-in for a long-running git command
+    let dir = TestRepo::empty_dir();
+    let shim = dir.path().join("git-shim");
+    std::fs::write(&shim, "#!/bin/sh\nexec sleep 30\n").expect("Error writing shim");
+    std::fs::set_permissions(&shim, std::fs::Permissions::from_mode(0o755))
+        .expect("Error making shim executable");
+
+    let config = GitConfig::new()
+        .with_program(&shim)
+        .with_timeout(Duration::from_millis(200));
+    let started = Instant::now();
+    match GitCommand::git_command_with::<String>(&config, "fetch", None, None, None) {
+        Ok(_) => panic!("Command didn't time out"),
+        Err(error) => assert_eq!(error.kind(), ErrorKind::TimedOut),
+    }
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    // A command that finishes in time isn't affected
+    let config = GitConfig::new().with_timeout(Duration::from_secs(30));
+    let output = GitCommand::git_command_with::<String>(&config, "--version", None, None, None)
+        .expect("Error running git");
+    assert!(output.starts_with("git version"));
+}
+
+#[cfg(unix)]
+#[test]
+fn stop_flag() {
+    use crate::test_repo::TestRepo;
+    use std::os::unix::fs::PermissionsExt;
+    use std::sync::Arc;
+
+    // A stand-in for a long-running git command
+    let dir = TestRepo::empty_dir();
+    let shim = dir.path().join("git-shim");
+    std::fs::write(&shim, "#!/bin/sh\nexec sleep 30\n").expect("Error writing shim");
+    std::fs::set_permissions(&shim, std::fs::Permissions::from_mode(0o755))
+        .expect("Error making shim executable");
+
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let config = GitConfig::new()
+        .with_program(&shim)
+        .with_stop_flag(stop_flag.clone());
+    let setter = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(200));
+        stop_flag.store(true, Ordering::SeqCst);
+    });
+    let started = Instant::now();
+    let result = GitCommand::git_command_with::<String>(&config, "fetch", None, None, None);
+    setter.join().expect("Error setting flag");
+
+    match result {
+        Ok(_) => panic!("Command wasn't cancelled"),
+        Err(error) => assert_eq!(error.kind(), ErrorKind::Cancelled),
+    }
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[cfg(unix)]
+#[test]
+fn stop_flag_to_writer() {
+    use crate::test_repo::TestRepo;
+    use std::os::unix::fs::PermissionsExt;
+    use std::sync::Arc;
+
+    // A stand-in for a long-running git command that streams some output first
+    let dir = TestRepo::empty_dir();
+    let shim = dir.path().join("git-shim");
+    std::fs::write(&shim, "#!/bin/sh\necho started\nexec sleep 30\n").expect("Error writing shim");
+    std::fs::set_permissions(&shim, std::fs::Permissions::from_mode(0o755))
+        .expect("Error making shim executable");
+
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let config = GitConfig::new()
+        .with_program(&shim)
+        .with_stop_flag(stop_flag.clone());
+    let setter = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(200));
+        stop_flag.store(true, Ordering::SeqCst);
+    });
+    let started = Instant::now();
+    let mut output = vec![];
+    let result = GitCommand::git_command_to_writer_with(&config, "fetch", None, &mut output, None);
+    setter.join().expect("Error setting flag");
+
+    match result {
+        Ok(_) => panic!("Command wasn't cancelled"),
+        Err(error) => assert_eq!(error.kind(), ErrorKind::Cancelled),
+    }
+    assert_eq!(output, b"started\n");
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    let config = GitConfig::new()
+        .with_program(&shim)
+        .with_timeout(Duration::from_millis(200));
+    match GitCommand::git_command_to_writer_with(&config, "fetch", None, &mut vec![], None) {
+        Ok(_) => panic!("Command didn't time out"),
+        Err(error) => assert_eq!(error.kind(), ErrorKind::TimedOut),
+    }
+}
+
+#[test]
+fn stop_flag_not_set() {
+    use std::sync::Arc;
+
+    let config = GitConfig::new().with_stop_flag(Arc::new(AtomicBool::new(false)));
+    let output = GitCommand::git_command_with::<String>(&config, "--version", None, None, None)
+        .expect("Error running git");
+    assert!(output.starts_with("git version"));
+}
+
+#[test]
+fn env() {
+    let config = GitConfig::new().with_env("GIT_CONFIG_PARAMETERS", "'test.key'='value'");
+    let output = GitCommand::git_command_with::<String>(
+        &config,
+        "config",
+        Some(vec!["--get", "test.key"]),
+        None,
+        None,
+    )
+    .expect("Error running git");
+    assert_eq!(output, "value\n");
+}
+
+#[test]
+fn git_not_found() {
+    use crate::test_repo::TestRepo;
+
+    let dir = TestRepo::empty_dir();
+    let config = GitConfig::new().with_program(dir.path().join("no-such-git"));
+    match GitCommand::git_command_with::<String>(&config, "--version", None, None, None) {
+        Ok(_) => panic!("Missing program was run"),
+        Err(error) => assert_eq!(error.kind(), ErrorKind::GitNotFound),
+    }
+
+    // A missing working directory isn't a missing program
+    let missing = dir.path().join("missing");
+    match GitCommand::git_command::<String>("--version", None, None, Some(&missing)) {
+        Ok(_) => panic!("Ran in a missing directory"),
+        Err(error) => assert_eq!(error.kind(), ErrorKind::Other),
+    }
 }
@@ -0,0 +1,129 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// A function that's called with the Git command and its arguments before each one is run
+pub type TraceHook = dyn Fn(&[&str]) + Send + Sync;
+
+/// The process-wide defaults, set by `GitCommand::configure`
+static DEFAULT_CONFIG: RwLock<GitConfig> = RwLock::new(GitConfig::new());
+
+/// Settings that control how Git is run.
+/// They can be set for the whole process with `GitCommand::configure`, or for a single command
+/// with `GitCommand::git_command_with`. Settings given for a single command take precedence
+/// over the process-wide ones.
+#[derive(Clone, Default)]
+pub struct GitConfig {
+    program: Option<PathBuf>,
+    env: Vec<(String, String)>,
+    stop_flag: Option<Arc<AtomicBool>>,
+    timeout: Option<Duration>,
+    trace: Option<Arc<TraceHook>>,
+}
+
+impl GitConfig {
+    pub const fn new() -> GitConfig {
+        GitConfig {
+            program: None,
+            env: Vec::new(),
+            stop_flag: None,
+            timeout: None,
+            trace: None,
+        }
+    }
+
+    /// Run this program instead of the `git` found on the path
+    pub fn with_program<T: Into<PathBuf>>(mut self, program: T) -> GitConfig {
+        self.program = Some(program.into());
+        self
+    }
+
+    /// Set an environment variable for the Git process
+    pub fn with_env<K: ToString, V: ToString>(mut self, key: K, value: V) -> GitConfig {
+        self.env.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Kill the Git process and fail with `ErrorKind::Cancelled` when this flag is set,
+    /// e.g. by a UI's cancel button on another thread
+    pub fn with_stop_flag(mut self, stop_flag: Arc<AtomicBool>) -> GitConfig {
+        self.stop_flag = Some(stop_flag);
+        self
+    }
+
+    /// Kill the Git process and fail with `ErrorKind::TimedOut` if it runs for longer than this
+    pub fn with_timeout(mut self, timeout: Duration) -> GitConfig {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Call this with the Git command and its arguments before each one is run, e.g. to log them
+    pub fn with_trace<F: Fn(&[&str]) + Send + Sync + 'static>(mut self, trace: F) -> GitConfig {
+        self.trace = Some(Arc::new(trace));
+        self
+    }
+
+    pub fn program(&self) -> &Option<PathBuf> {
+        &self.program
+    }
+    pub fn env(&self) -> &Vec<(String, String)> {
+        &self.env
+    }
+    pub fn stop_flag(&self) -> Option<&AtomicBool> {
+        self.stop_flag.as_deref()
+    }
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+    pub fn trace(&self) -> Option<&TraceHook> {
+        self.trace.as_deref()
+    }
+
+    /// Returns these settings, with any that aren't set taken from the defaults.
+    /// Environment variables from both are used, with these taking precedence.
+    pub fn or(&self, defaults: &GitConfig) -> GitConfig {
+        GitConfig {
+            program: self.program.clone().or_else(|| defaults.program.clone()),
+            env: defaults.env.iter().chain(&self.env).cloned().collect(),
+            stop_flag: self
+                .stop_flag
+                .clone()
+                .or_else(|| defaults.stop_flag.clone()),
+            timeout: self.timeout.or(defaults.timeout),
+            trace: self.trace.clone().or_else(|| defaults.trace.clone()),
+        }
+    }
+
+    pub(crate) fn set_default(config: GitConfig) {
+        match DEFAULT_CONFIG.write() {
+            Ok(mut default) => *default = config,
+            Err(poisoned) => *poisoned.into_inner() = config,
+        }
+    }
+
+    pub(crate) fn default_config() -> GitConfig {
+        match DEFAULT_CONFIG.read() {
+            Ok(default) => default.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+}
+
+impl fmt::Debug for GitConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GitConfig")
+            .field("program", &self.program)
+            .field("env", &self.env)
+            .field("stop_flag", &self.stop_flag)
+            .field("timeout", &self.timeout)
+            .field("trace", &self.trace.is_some())
+            .finish()
+    }
+}
@@ -5,13 +5,40 @@
 
 use std::fmt;
 
+/// Broad categories of failure, so callers can react to specific conditions
+/// without matching on Git's message text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Any failure that doesn't have a more specific kind
+    Other,
+    /// The repository doesn't have any commits yet
+    NoCommits,
+    /// There isn't a merge, rebase, etc. in progress to abort
+    NothingToAbort,
+    /// The command was cancelled with a stop flag
+    Cancelled,
+    /// The command ran for longer than its timeout and was killed
+    TimedOut,
+    /// The Git program couldn't be found, probably because Git isn't installed
+    GitNotFound,
+}
+
 pub struct Error {
+    kind: ErrorKind,
     message: String,
 }
 
 impl Error {
     pub fn new(message: String) -> Error {
-        Error { message }
+        Error::with_kind(ErrorKind::Other, message)
+    }
+
+    pub fn with_kind(kind: ErrorKind, message: String) -> Error {
+        Error { kind, message }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
     }
 
     pub fn message(&self) -> &str {
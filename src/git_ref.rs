@@ -0,0 +1,37 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+/// A ref, such as a branch or tag, and the object it points to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ref {
+    name: String,
+    oid: String,
+}
+
+impl Ref {
+    /// The `--format` that produces the output `from_for_each_ref` parses
+    pub(crate) const FORMAT: &'static str = "--format=%(refname)%00%(objectname)";
+
+    /// The full name of the ref, e.g. `refs/heads/main`
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+    /// The OID of the object the ref points to. For an annotated tag, that's the tag object.
+    pub fn oid(&self) -> &str {
+        self.oid.as_str()
+    }
+
+    /// Parses the output of `git for-each-ref`, run with `Ref::FORMAT`
+    pub(crate) fn from_for_each_ref(output: &str) -> Vec<Ref> {
+        output
+            .lines()
+            .filter_map(|line| line.split_once('\0'))
+            .map(|(name, oid)| Ref {
+                name: name.to_owned(),
+                oid: oid.to_owned(),
+            })
+            .collect()
+    }
+}
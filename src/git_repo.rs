@@ -0,0 +1,43 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+use crate::git_command::Error as GitCommandError;
+use crate::GitLib;
+use std::path::PathBuf;
+
+/// A Git working directory that is known to exist
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GitRepo {
+    path: PathBuf,
+}
+
+impl GitRepo {
+    /// Opens the working directory that contains a path, failing if the path isn't in one.
+    /// The repository's path is its top level folder, no matter where in it the given path is.
+    pub fn open(path: &PathBuf) -> Result<GitRepo, GitCommandError> {
+        let path = GitLib::top_level(Some(path))?;
+        Ok(GitRepo { path })
+    }
+
+    /// The top level folder of the working directory
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+}
+
+#[test]
+fn open() {
+    let repo = crate::test_repo::TestRepo::new();
+    let subdir = repo.path().join("subdir");
+    std::fs::create_dir(&subdir).expect("Error creating subdir");
+    let git_repo = GitRepo::open(&subdir).expect("Error opening repo");
+    assert_eq!(git_repo.path(), repo.path());
+}
+
+#[test]
+fn open_not_repo() {
+    let dir = crate::test_repo::TestRepo::empty_dir();
+    assert!(GitRepo::open(dir.path()).is_err());
+}
@@ -0,0 +1,52 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+/// The name, email and (optionally) date to record as a commit's author or committer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Identity {
+    name: String,
+    email: String,
+    date: Option<String>,
+}
+
+impl Identity {
+    pub fn new<T: ToString>(name: T, email: T) -> Identity {
+        Identity {
+            name: name.to_string(),
+            email: email.to_string(),
+            date: None,
+        }
+    }
+
+    /// Use this date instead of the current time. It can be in any format Git accepts,
+    /// e.g. ISO 8601 (`2020-01-02T03:04:05Z`) or RFC 2822.
+    pub fn with_date<T: ToString>(mut self, date: T) -> Identity {
+        self.date = Some(date.to_string());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+    pub fn email(&self) -> &str {
+        self.email.as_str()
+    }
+    pub fn date(&self) -> &Option<String> {
+        &self.date
+    }
+
+    /// Returns the environment variables that give this identity to a Git process,
+    /// where `role` is `AUTHOR` or `COMMITTER`
+    pub(crate) fn env(&self, role: &str) -> Vec<(String, String)> {
+        let mut env = vec![
+            (format!("GIT_{role}_NAME"), self.name.clone()),
+            (format!("GIT_{role}_EMAIL"), self.email.clone()),
+        ];
+        if let Some(date) = &self.date {
+            env.push((format!("GIT_{role}_DATE"), date.clone()));
+        }
+        env
+    }
+}
@@ -3,13 +3,70 @@
  * Created 2024-02-09
  */
 
+pub use crate::blame_line::BlameLine;
+pub use crate::change_kind::ChangeKind;
+pub use crate::commit::Commit;
+pub use crate::config_entry::ConfigEntry;
+pub use crate::config_scope::ConfigScope;
+pub use crate::conflict_versions::ConflictVersions;
+pub use crate::credential_parse_error::CredentialParseError;
 pub use crate::credentials::Credentials;
-use crate::git_command::{Error as GitCommandError, GitCommand};
-use std::path::PathBuf;
+pub use crate::force_mode::ForceMode;
+pub use crate::full_commit::FullCommit;
+use crate::git_command::{Error as GitCommandError, ErrorKind, GitCommand, GitConfig};
+pub use crate::git_ref::Ref;
+pub use crate::git_repo::GitRepo;
+pub use crate::identity::Identity;
+pub use crate::object_type::ObjectType;
+pub use crate::pack_stats::PackStats;
+pub use crate::ref_kind::RefKind;
+pub use crate::repo_state::RepoState;
+pub use crate::revert_outcome::RevertOutcome;
+pub use crate::signature_status::SignatureStatus;
+pub use crate::stash_outcome::StashOutcome;
+pub use crate::status_entry::StatusEntry;
+pub use crate::submodule_state::SubmoduleState;
+pub use crate::submodule_status::SubmoduleStatus;
+pub use crate::tag_info::TagInfo;
+pub use crate::tree_entry::TreeEntry;
+pub use crate::whitespace_issue::WhitespaceIssue;
+pub use crate::worktree::Worktree;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 
+pub mod blame_line;
+pub mod change_kind;
+pub mod commit;
+pub mod config_entry;
+pub mod config_scope;
+pub mod conflict_versions;
+pub mod credential_parse_error;
 pub mod credentials;
+pub mod force_mode;
+pub mod full_commit;
 pub mod git_command;
+pub mod git_ref;
+pub mod git_repo;
+pub mod identity;
+pub mod object_type;
+pub mod pack_stats;
+pub mod ref_kind;
+pub mod repo_state;
+pub mod revert_outcome;
+pub mod signature_status;
+pub mod stash_outcome;
+pub mod status_entry;
+pub mod submodule_state;
+pub mod submodule_status;
+pub mod tag_info;
+#[cfg(test)]
+mod test_repo;
+pub mod tree_entry;
+pub mod whitespace_issue;
+pub mod worktree;
 
 pub struct GitLib {}
 
@@ -29,6 +86,85 @@ impl GitLib {
         Ok(())
     }
 
+    /// Fetch from all of a local repository's remotes, optionally pruning remote-tracking
+    /// branches that no longer exist on the remote.
+    /// If any remote fails, the error describes all of the failures Git reported.
+    pub fn fetch_all(prune: bool, path: Option<&PathBuf>) -> Result<(), GitCommandError> {
+        let mut args = vec!["--all", "--quiet"];
+        if prune {
+            args.push("--prune");
+        }
+        let _ = GitCommand::git_command::<String>(
+            "fetch",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Push a branch (or any refspec) to a remote
+    pub fn push(
+        remote: &str,
+        refspec: &str,
+        force: ForceMode,
+        path: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let mut args = vec!["--quiet"];
+        if let Some(arg) = force.arg() {
+            args.push(arg);
+        }
+        args.extend([remote, refspec]);
+        let _ = GitCommand::git_command::<String>(
+            "push",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Delete a branch on a remote
+    pub fn push_delete_branch(
+        remote: &str,
+        branch: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let _ = GitCommand::git_command::<String>(
+            "push",
+            Some(vec!["--quiet", remote, "--delete", branch]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Ask a remote for the SHA one of its refs points to, without fetching anything.
+    /// The ref can be a full name like `refs/heads/main`, or a shorter one like `main`, which
+    /// matches the same way `git ls-remote` does. Returns `None` if the remote doesn't have the ref.
+    pub fn remote_head_oid(
+        remote: &str,
+        ref_name: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<Option<String>, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "ls-remote",
+            Some(vec![remote, ref_name]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        let refs: Vec<(&str, &str)> = output
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .collect();
+        // Prefer an exact match over one that only matches the end of the name
+        let found = refs
+            .iter()
+            .find(|(_, name)| *name == ref_name)
+            .or_else(|| refs.first());
+        Ok(found.map(|(oid, _)| oid.to_string()))
+    }
+
     /// Ask Git for the first URL of the named remote
     pub fn remote_url(repo: &str, path: Option<&PathBuf>) -> Result<String, GitCommandError> {
         let output = GitCommand::git_command::<String>(
@@ -68,154 +204,4635 @@ impl GitLib {
         }
     }
 
-    /// Ask Git for the top level folder of a path.
-    /// Ask Git to fill in the username and password for the given url and return the full credentials.
-    pub fn credentials_fill(url: &str) -> Result<Credentials, GitCommandError> {
-        let credentials = Credentials::with_url(url);
-        let output =
-            GitCommand::git_command("credential", Some(vec!["fill"]), Some(credentials), None)?;
-        match Credentials::from_str(output.as_str()) {
-            Ok(credentials) => Ok(credentials),
-            Err(_) => Err(GitCommandError::new(
-                "Failed converting output to credentials".to_string(),
-            )),
+    /// Ask Git for the absolute path of the Git directory (usually `.git`) related to a path.
+    pub fn git_dir(path: Option<&PathBuf>) -> Result<PathBuf, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "rev-parse",
+            Some(vec!["--absolute-git-dir"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        match PathBuf::from_str(output.trim_end_matches('\n')) {
+            Ok(path) => Ok(path),
+            Err(error) => Err(GitCommandError::new(error.to_string())),
         }
     }
 
-    /// Tell Git that the given credentials were accepted by an operation.
-    pub fn credentials_approve(credentials: &Credentials) -> Result<(), GitCommandError> {
-        let _ =
-            GitCommand::git_command("credential", Some(vec!["approve"]), Some(credentials), None)?;
-        Ok(())
+    /// Find out whether a merge, rebase, cherry-pick, revert or bisect is in progress.
+    pub fn repo_state(path: Option<&PathBuf>) -> Result<RepoState, GitCommandError> {
+        let git_dir = Self::git_dir(path)?;
+        Ok(RepoState::from_git_dir(&git_dir))
     }
 
-    /// Tell Git that the given credentials were rejected by an operation.
-    pub fn credentials_reject(credentials: &Credentials) -> Result<(), GitCommandError> {
-        let _ =
-            GitCommand::git_command("credential", Some(vec!["reject"]), Some(credentials), None)?;
+    /// Abandon whatever merge, rebase, am, cherry-pick, revert or bisect is in progress and return to
+    /// the state before it started.
+    /// Fails with `ErrorKind::NothingToAbort` if nothing is in progress.
+    pub fn abort_current(path: Option<&PathBuf>) -> Result<(), GitCommandError> {
+        let (command, args) = match Self::repo_state(path)? {
+            RepoState::Merge => ("merge", vec!["--abort"]),
+            RepoState::Rebase => ("rebase", vec!["--abort"]),
+            RepoState::Am => ("am", vec!["--abort"]),
+            RepoState::CherryPick => ("cherry-pick", vec!["--abort"]),
+            RepoState::Revert => ("revert", vec!["--abort"]),
+            RepoState::Bisect => ("bisect", vec!["reset"]),
+            RepoState::Clean => {
+                return Err(GitCommandError::with_kind(
+                    ErrorKind::NothingToAbort,
+                    "Nothing to abort".to_string(),
+                ))
+            }
+        };
+        let _ = GitCommand::git_command::<String>(
+            command,
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
         Ok(())
     }
-}
 
-impl GitLib {
-    fn path(path: Option<PathBuf>) -> PathBuf {
-        if let Some(path) = path {
-            path
+    /// Revert a commit, optionally leaving the changes staged instead of committing them.
+    /// If the revert stops because of conflicts, the conflicted files are returned and the
+    /// revert is left in progress to be resolved or aborted.
+    pub fn revert(
+        commit: &str,
+        no_commit: bool,
+        path: Option<&PathBuf>,
+    ) -> Result<RevertOutcome, GitCommandError> {
+        let mut args = vec!["--no-edit"];
+        if no_commit {
+            args.push("--no-commit");
+        }
+        args.push(commit);
+        let output = GitCommand::run_git_command::<String>(
+            "revert",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        if output.status.success() {
+            return if no_commit {
+                Ok(RevertOutcome::Staged)
+            } else {
+                Ok(RevertOutcome::Reverted(Self::head_sha(path)?))
+            };
+        }
+        let conflicts = Self::unmerged_files(path)?;
+        if conflicts.is_empty() {
+            Err(GitCommand::stderr_error(&output))
         } else {
-            std::env::current_dir().expect("Error getting cwd")
+            Ok(RevertOutcome::Conflict(conflicts))
         }
     }
-}
 
-// All of these tests suck....
-#[test]
-// This is not appropriate for automated testing, because it relies on user input.
-// What's more, it requires that the enters specific values.
-fn credential() {
-    let url = "http://example.com";
-    let username = "baravelli";
-    let password = "swordfish";
+    /// Ask Git for the conflicted files whose resolutions rerere (reuse recorded resolution)
+    /// is recording. Rerere must be enabled with the `rerere.enabled` setting.
+    pub fn rerere_status(path: Option<&PathBuf>) -> Result<Vec<PathBuf>, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "rerere",
+            Some(vec!["status"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(output.lines().map(PathBuf::from).collect())
+    }
 
-    // Get the username and password for the url from the user.
-    // The user is expected to enter the values above.
-    let credentials = GitLib::credentials_fill(url).expect("Failed to fill credentials (prompt)");
+    /// Make rerere forget the resolutions it is recording for the current conflict.
+    pub fn rerere_clear(path: Option<&PathBuf>) -> Result<(), GitCommandError> {
+        let _ = GitCommand::git_command::<String>(
+            "rerere",
+            Some(vec!["clear"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(())
+    }
 
-    // Store the credentials for the url in the credential manager
-    GitLib::credentials_approve(&credentials).expect("Failed to approve credentials");
+    /// Start a bisect between a known bad and a known good commit.
+    pub fn bisect_start(
+        bad: &str,
+        good: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let _ = GitCommand::git_command::<String>(
+            "bisect",
+            Some(vec!["start", bad, good]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(())
+    }
 
-    // Get the credentials for the url again.
-    // This time they should come from the credential manager.
-    let credentials_fill =
-        GitLib::credentials_fill(url).expect("Failed to fill credentials (approved)");
+    /// Let a script find the first bad commit of a bisect that has been started, and return its SHA.
+    /// The script must exit with 0 if a commit is good, 125 if it can't be tested, and any other
+    /// code between 1 and 127 if it is bad.
+    pub fn bisect_run(script: &Path, path: Option<&PathBuf>) -> Result<String, GitCommandError> {
+        let script = Self::path_str(script)?;
+        let output = GitCommand::git_command::<String>(
+            "bisect",
+            Some(vec!["run", script]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        match output
+            .lines()
+            .find_map(|line| line.strip_suffix(" is the first bad commit"))
+        {
+            Some(sha) => Ok(sha.to_owned()),
+            None => Err(GitCommandError::new(
+                "Bisect didn't find the first bad commit".to_string(),
+            )),
+        }
+    }
 
-    // Verify that the username and password are what are expected
-    assert_eq!(
-        credentials_fill.username().as_ref(),
-        Some(&username.to_string())
-    );
-    assert_eq!(
-        credentials_fill.password().as_ref(),
-        Some(&password.to_string())
-    );
+    /// End a bisect and return to the commit that was checked out before it started.
+    pub fn bisect_reset(path: Option<&PathBuf>) -> Result<(), GitCommandError> {
+        let _ = GitCommand::git_command::<String>(
+            "bisect",
+            Some(vec!["reset"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(())
+    }
 
-    // Remove the credentials for the url from the credential manager
-    GitLib::credentials_reject(&credentials_fill).expect("Failed to reject credentials");
+    /// Ask Git for all of the configuration in effect for a path, as key/value pairs.
+    /// Keys that are set more than once appear once for each value.
+    /// A key that is set without a value, which Git treats as true, has an empty value.
+    pub fn config_list(path: Option<&PathBuf>) -> Result<Vec<(String, String)>, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "config",
+            Some(vec!["--list", "-z"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(output
+            .split('\0')
+            .filter(|record| !record.is_empty())
+            .map(Self::config_key_value)
+            .collect())
+    }
 
-    // If the above worked, the credential manager will prompt the user again.
-    // The user is expected to cancel the dialog.
-    let credentials_fill =
-        GitLib::credentials_fill(url).expect("Failed to fill credentials (rejected)");
-    assert_eq!(credentials_fill.username().as_ref(), Some(&"".to_string()));
-    assert_eq!(credentials_fill.password().as_ref(), Some(&"".to_string()));
-}
+    /// Ask Git for all of the configuration in effect for a path, along with where each value
+    /// was set (system, global or local config files, the command line, etc.).
+    pub fn config_list_origins(
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<ConfigEntry>, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "config",
+            Some(vec!["--list", "--show-origin", "-z"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(ConfigEntry::from_list_z(&output))
+    }
 
-#[test]
-fn is_in_work_tree() {
-    assert!(GitLib::is_inside_work_tree(None) // cwd
-        .expect("Not inside work tree"));
-}
+    /// Ask Git for a boolean configuration value, normalized the way Git does it, so that e.g.
+    /// `yes`, `on` and `1` are all true. Returns `None` if the key isn't set.
+    pub fn config_get_bool(
+        key: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<Option<bool>, GitCommandError> {
+        match Self::config_get_typed(key, Some("bool"), None, path)? {
+            Some(value) => Ok(Some(value == "true")),
+            None => Ok(None),
+        }
+    }
 
-#[test]
-fn is_in_work_tree_cwd() {
-    assert!(GitLib::is_inside_work_tree(Some(
-        &std::env::current_dir().expect("Error getting cwd")
-    ))
-    .expect("Not inside work tree"));
-}
+    /// Ask Git for an integer configuration value, normalized the way Git does it, so that e.g.
+    /// `1k` is 1024. Returns `None` if the key isn't set.
+    pub fn config_get_int(
+        key: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<Option<i64>, GitCommandError> {
+        match Self::config_get_typed(key, Some("int"), None, path)? {
+            Some(value) => match value.parse::<i64>() {
+                Ok(value) => Ok(Some(value)),
+                Err(error) => Err(GitCommandError::new(error.to_string())),
+            },
+            None => Ok(None),
+        }
+    }
 
-#[test]
-fn top_level() {
-    // cargo test --lib top_level -- --show-output
-    let cwd = std::env::current_dir().expect("Error getting cwd");
-    let mut dir = cwd.clone();
-    dir.push("src");
-    let top_level = GitLib::top_level(Some(&dir)).expect("No top level");
-    assert_eq!(cwd, top_level)
-}
+    /// Ask Git for the full SHA of the current commit.
+    /// Fails with `ErrorKind::NoCommits` if the current branch doesn't have any commits yet.
+    pub fn head_sha(path: Option<&PathBuf>) -> Result<String, GitCommandError> {
+        let output = GitCommand::run_git_command::<String>(
+            "rev-parse",
+            Some(vec!["--verify", "--quiet", "HEAD"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        match output.status.code() {
+            Some(0) => Ok(GitCommand::stdout(&output)?
+                .trim_end_matches('\n')
+                .to_owned()),
+            // --verify --quiet exits with 1 and no message when HEAD doesn't resolve
+            Some(1) if output.stderr.is_empty() => Err(GitCommandError::with_kind(
+                ErrorKind::NoCommits,
+                "No commits yet".to_string(),
+            )),
+            _ => Err(GitCommand::stderr_error(&output)),
+        }
+    }
 
-#[test]
-fn remote_url() {
-    const REMOTE_NAME: &str = "origin";
-    const TEST_PATH: &str = "C:\\Users\\psobo\\Development\\rust\\ckpath";
-    const REMOTE_URL: &str = "http://marconi/gitea/psobolik/ckpath-rust.git";
+    /// Ask Git for the SHA of the initial commit of the current branch's history.
+    /// If the history has more than one root commit (e.g. from merging unrelated histories),
+    /// the oldest is returned.
+    /// Fails with `ErrorKind::NoCommits` if the current branch doesn't have any commits yet.
+    pub fn root_commit(path: Option<&PathBuf>) -> Result<String, GitCommandError> {
+        let head = Self::head_sha(path)?;
+        let output = GitCommand::git_command::<String>(
+            "rev-list",
+            Some(vec!["--max-parents=0", &head, "--"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // Newest first
+        match output.lines().last() {
+            Some(root) => Ok(root.to_owned()),
+            None => Err(GitCommandError::new("No root commit".to_string())),
+        }
+    }
 
-    let mut path = PathBuf::new();
-    path.push(TEST_PATH);
-    let remote_url =
-        GitLib::remote_url(REMOTE_NAME, Some(&path)).expect("Error getting remove URL");
-    assert_eq!(remote_url, REMOTE_URL);
-}
+    /// Ask Git if HEAD is detached, i.e. doesn't refer to a branch.
+    pub fn is_detached_head(path: Option<&PathBuf>) -> Result<bool, GitCommandError> {
+        let output = GitCommand::run_git_command::<String>(
+            "symbolic-ref",
+            Some(vec!["--quiet", "HEAD"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        match output.status.code() {
+            Some(0) => Ok(false),
+            // --quiet exits with 1 and no message when HEAD isn't a symbolic ref
+            Some(1) if output.stderr.is_empty() => Ok(true),
+            _ => Err(GitCommand::stderr_error(&output)),
+        }
+    }
 
-#[test]
-#[should_panic]
-fn no_remote_url() {
-    const REMOTE_NAME: &str = "origin";
-    const TEST_PATH: &str = "C:\\Users\\psobo\\Development\\rust";
+    /// Stash the current changes and revert them from the working tree.
+    /// With `include_untracked`, untracked files are stashed (and removed) too, and with
+    /// `keep_index`, changes that are staged are left in place as well as being stashed.
+    pub fn stash_push(
+        message: Option<&str>,
+        include_untracked: bool,
+        keep_index: bool,
+        path: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let mut args = vec!["push", "--quiet"];
+        if include_untracked {
+            args.push("--include-untracked");
+        }
+        if keep_index {
+            args.push("--keep-index");
+        }
+        if let Some(message) = message {
+            args.extend(["--message", message]);
+        }
+        let _ = GitCommand::git_command::<String>(
+            "stash",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(())
+    }
 
-    match GitLib::remote_url(REMOTE_NAME, Some(&PathBuf::from(TEST_PATH))) {
-        Ok(_) => {}
-        Err(error) => panic!("{:?}", error),
+    /// Ask Git how many entries there are in the stash list.
+    pub fn stash_count(path: Option<&PathBuf>) -> Result<usize, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "stash",
+            Some(vec!["list", "--format=%gd"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(output.lines().count())
     }
-}
 
-#[test]
-#[should_panic]
-fn not_is_in_work_tree() {
-    const TEST_PATH: &str = "C:\\Users\\psobo\\Development\\rust";
+    /// Ask Git for the changes recorded in a stash entry, as a patch.
+    pub fn stash_show(index: usize, path: Option<&PathBuf>) -> Result<String, GitCommandError> {
+        let stash = Self::stash_ref(index);
+        GitCommand::git_command::<String>(
+            "stash",
+            Some(vec!["show", "-p", &stash]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )
+    }
 
-    assert!(
-        !GitLib::is_inside_work_tree(Some(&PathBuf::from(TEST_PATH)))
-            .expect("Not inside work tree")
-    );
-}
+    /// Ask Git for the SHAs of the commits in a range (e.g. `main..feature`), newest first,
+    /// optionally limited to a number of commits.
+    pub fn rev_list(
+        range: &str,
+        limit: Option<u32>,
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<String>, GitCommandError> {
+        let limit = limit.map(|limit| format!("--max-count={limit}"));
+        let mut args = vec![];
+        if let Some(limit) = &limit {
+            args.push(limit.as_str());
+        }
+        args.extend([range, "--"]);
+        let output = GitCommand::git_command::<String>(
+            "rev-list",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(output.lines().map(|line| line.to_owned()).collect())
+    }
 
-#[test]
-#[should_panic]
-fn no_top_level() {
+    /// Ask Git which files a single commit changed, compared to its first parent.
+    /// A root commit has no parent, so it's compared to the empty tree, and all of its files are
+    /// reported as added.
+    pub fn commit_changed_files(
+        rev: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<StatusEntry>, GitCommandError> {
+        let dir = Self::path(path.cloned());
+        let root = GitCommand::git_command::<String>(
+            "rev-list",
+            Some(vec!["--no-walk", "--max-parents=0", rev, "--"]),
+            None,
+            Some(&dir),
+        )?;
+        let empty_tree = if root.is_empty() {
+            None
+        } else {
+            Some(Self::empty_tree_oid(path)?)
+        };
+        let mut args = vec!["--no-commit-id", "--name-status", "-r", "-M", "-z"];
+        if let Some(empty_tree) = &empty_tree {
+            args.push(empty_tree);
+        }
+        args.push(rev);
+        let output = GitCommand::git_command::<String>("diff-tree", Some(args), None, Some(&dir))?;
+        Ok(StatusEntry::from_name_status_z(&output))
+    }
+
+    /// Ask Git for the differences between a stash entry and the working tree, as a patch.
+    /// Unlike `stash_show`, which shows what the stash changed, this shows what would have to
+    /// change to get from the stash to the current work.
+    pub fn diff_stash(index: usize, path: Option<&PathBuf>) -> Result<String, GitCommandError> {
+        let stash = Self::stash_ref(index);
+        GitCommand::git_command::<String>(
+            "diff",
+            Some(vec![&stash, "--"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )
+    }
+
+    /// Create a branch starting at the commit a stash entry was made from, check it out and apply
+    /// the stash to it, dropping the stash if it applies cleanly.
+    /// Uses the latest stash entry if no index is given.
+    pub fn stash_branch(
+        branch: &str,
+        index: Option<usize>,
+        path: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let stash = index.map(Self::stash_ref);
+        let mut args = vec!["branch", branch];
+        if let Some(stash) = &stash {
+            args.push(stash);
+        }
+        let _ = GitCommand::git_command::<String>(
+            "stash",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Create a stash commit for the current changes, without storing it in the stash list or
+    /// touching the working tree, and return its SHA.
+    /// Returns `None` if there aren't any changes to stash.
+    pub fn stash_create(
+        message: Option<&str>,
+        path: Option<&PathBuf>,
+    ) -> Result<Option<String>, GitCommandError> {
+        let mut args = vec!["create"];
+        if let Some(message) = message {
+            args.push(message);
+        }
+        let output = GitCommand::git_command::<String>(
+            "stash",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        let oid = output.trim_end_matches('\n');
+        if oid.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(oid.to_owned()))
+        }
+    }
+
+    /// Store a stash commit (e.g. one made by `stash_create`) in the stash list.
+    pub fn stash_store(
+        oid: &str,
+        message: Option<&str>,
+        path: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let mut args = vec!["store", "--quiet"];
+        if let Some(message) = message {
+            args.extend(["--message", message]);
+        }
+        args.push(oid);
+        let _ = GitCommand::git_command::<String>(
+            "stash",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Ask Git for the working trees attached to a repository, starting with the main one.
+    pub fn worktree_list(repo: Option<&PathBuf>) -> Result<Vec<Worktree>, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "worktree",
+            Some(vec!["list", "--porcelain", "-z"]),
+            None,
+            Some(&Self::path(repo.cloned())),
+        )?;
+        Ok(Worktree::from_porcelain_z(&output))
+    }
+
+    /// Lock a linked working tree so that it isn't pruned, moved or deleted,
+    /// e.g. because it is on removable media. The reason is shown by `worktree_list`.
+    pub fn worktree_lock(
+        path: &Path,
+        reason: Option<&str>,
+        repo: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let path = Self::path_str(path)?;
+        let mut args = vec!["lock"];
+        if let Some(reason) = reason {
+            args.extend(["--reason", reason]);
+        }
+        args.push(path);
+        let _ = GitCommand::git_command::<String>(
+            "worktree",
+            Some(args),
+            None,
+            Some(&Self::path(repo.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Move a linked working tree to a new location, updating Git's records of it.
+    /// Locked working trees can't be moved.
+    pub fn worktree_move(
+        from: &Path,
+        to: &Path,
+        repo: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let _ = GitCommand::git_command::<String>(
+            "worktree",
+            Some(vec!["move", Self::path_str(from)?, Self::path_str(to)?]),
+            None,
+            Some(&Self::path(repo.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Unlock a linked working tree.
+    pub fn worktree_unlock(path: &Path, repo: Option<&PathBuf>) -> Result<(), GitCommandError> {
+        let _ = GitCommand::git_command::<String>(
+            "worktree",
+            Some(vec!["unlock", Self::path_str(path)?]),
+            None,
+            Some(&Self::path(repo.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Ask Git to compare two versions of a series of commits, e.g. a branch before and after it
+    /// was rebased, where both versions start from the same base.
+    pub fn range_diff(
+        base: &str,
+        old_tip: &str,
+        new_tip: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<String, GitCommandError> {
+        let old_range = format!("{base}..{old_tip}");
+        let new_range = format!("{base}..{new_tip}");
+        GitCommand::git_command::<String>(
+            "range-diff",
+            Some(vec!["--no-color", &old_range, &new_range]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )
+    }
+
+    /// Ask Git to draw the history of HEAD as an ASCII graph, one commit per line with its
+    /// abbreviated SHA, ref names and subject. Optionally limited to a number of commits.
+    pub fn log_graph(
+        limit: Option<u32>,
+        path: Option<&PathBuf>,
+    ) -> Result<String, GitCommandError> {
+        let limit = limit.map(|limit| format!("--max-count={limit}"));
+        let mut args = vec!["--graph", "--oneline", "--decorate", "--no-color"];
+        if let Some(limit) = &limit {
+            args.push(limit.as_str());
+        }
+        GitCommand::git_command::<String>("log", Some(args), None, Some(&Self::path(path.cloned())))
+    }
+
+    /// Ask Git for the commits that changed a file, newest first, following the file's history
+    /// across renames. Optionally limited to a number of commits.
+    pub fn path_history(
+        file: &str,
+        limit: Option<u32>,
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<Commit>, GitCommandError> {
+        let limit = limit.map(|limit| format!("--max-count={limit}"));
+        let mut args = vec!["--follow", Commit::FORMAT];
+        if let Some(limit) = &limit {
+            args.push(limit.as_str());
+        }
+        args.extend(["--", file]);
+        let output = GitCommand::git_command::<String>(
+            "log",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(Commit::from_log(&output))
+    }
+
+    /// Ask Git if there are any changes, either in the working tree compared to the index, or
+    /// if `staged` is true, in the index compared to HEAD.
+    pub fn diff_has_changes(staged: bool, path: Option<&PathBuf>) -> Result<bool, GitCommandError> {
+        let mut args = vec!["--quiet"];
+        if staged {
+            args.push("--cached");
+        }
+        let output = GitCommand::run_git_command::<String>(
+            "diff",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // --quiet exits with 1 if there are changes and 0 if there aren't
+        match output.status.code() {
+            Some(0) => Ok(false),
+            Some(1) => Ok(true),
+            _ => Err(GitCommand::stderr_error(&output)),
+        }
+    }
+
+    /// Ask Git for the full message of a commit, including the body.
+    pub fn commit_message(rev: &str, path: Option<&PathBuf>) -> Result<String, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "log",
+            Some(vec!["-1", "--format=%B", rev, "--"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // Git adds a newline after the message
+        match output.strip_suffix('\n') {
+            Some(message) => Ok(message.to_owned()),
+            None => Ok(output),
+        }
+    }
+
+    /// Commit the staged changes and return the new commit's SHA.
+    /// The author and committer default to the configured user, but can be given explicitly,
+    /// e.g. to replay historical commits with their original identities and dates. They are
+    /// passed to Git in its environment rather than by changing the configuration, so commits
+    /// with different identities can safely be made in parallel.
+    /// Git refuses to make a commit that doesn't change anything, unless `allow_empty` is set,
+    /// which is useful for marker commits, e.g. to trigger a CI build.
+    pub fn commit(
+        message: &str,
+        author: Option<&Identity>,
+        committer: Option<&Identity>,
+        allow_empty: bool,
+        path: Option<&PathBuf>,
+    ) -> Result<String, GitCommandError> {
+        let mut config = GitConfig::new();
+        for (role, identity) in [("AUTHOR", author), ("COMMITTER", committer)] {
+            if let Some(identity) = identity {
+                for (key, value) in identity.env(role) {
+                    config = config.with_env(key, value);
+                }
+            }
+        }
+        let mut args = vec!["--quiet", "--message", message];
+        if allow_empty {
+            args.push("--allow-empty");
+        }
+        let _ = GitCommand::git_command_with::<String>(
+            &config,
+            "commit",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Self::head_sha(path)
+    }
+
+    /// Ask Git for everything about a commit: its tree, parents, author, committer and message.
+    pub fn get_commit(rev: &str, path: Option<&PathBuf>) -> Result<FullCommit, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "log",
+            Some(vec!["-1", FullCommit::FORMAT, rev, "--"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        match FullCommit::from_log(&output) {
+            Some(commit) => Ok(commit),
+            None => Err(GitCommandError::new(
+                "Failed converting output to commit".to_string(),
+            )),
+        }
+    }
+
+    /// Ask Git for the trailers at the end of a commit's message, e.g. `Signed-off-by`,
+    /// as key/value pairs in the order they appear.
+    pub fn commit_trailers(
+        rev: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<(String, String)>, GitCommandError> {
+        let message = Self::commit_message(rev, path)?;
+        let output = GitCommand::git_command(
+            "interpret-trailers",
+            Some(vec!["--parse"]),
+            Some(message),
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(output
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.trim().to_owned(), value.trim().to_owned()))
+            .collect())
+    }
+
+    /// Ask Git to add a trailer (e.g. `Signed-off-by`) to a commit message, following the
+    /// message's existing trailer block if it has one, and return the new message.
+    pub fn add_trailer(message: &str, key: &str, value: &str) -> Result<String, GitCommandError> {
+        let trailer = format!("{key}: {value}");
+        GitCommand::git_command(
+            "interpret-trailers",
+            Some(vec!["--trailer", &trailer]),
+            // The payload gets a newline of its own
+            Some(message.trim_end_matches('\n')),
+            None,
+        )
+    }
+
+    /// Ask Git for the upstream of the current branch, e.g. `origin/main`.
+    /// Returns `None` if the current branch doesn't have an upstream, or HEAD is detached.
+    pub fn current_upstream(path: Option<&PathBuf>) -> Result<Option<String>, GitCommandError> {
+        let output = GitCommand::run_git_command::<String>(
+            "rev-parse",
+            Some(vec!["--abbrev-ref", "--symbolic-full-name", "@{upstream}"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        if output.status.success() {
+            return Ok(Some(
+                GitCommand::stdout(&output)?
+                    .trim_end_matches('\n')
+                    .to_owned(),
+            ));
+        }
+        // Git fails the same way whether there's no upstream or no repository at all,
+        // so make sure there is a repository before deciding there's no upstream.
+        let _ = Self::git_dir(path)?;
+        Ok(None)
+    }
+
+    /// Ask Git for the size in bytes of an object, e.g. a blob, without reading its contents.
+    pub fn object_size(oid: &str, path: Option<&PathBuf>) -> Result<u64, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "cat-file",
+            Some(vec!["-s", oid]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        match output.trim_end_matches('\n').parse::<u64>() {
+            Ok(size) => Ok(size),
+            Err(error) => Err(GitCommandError::new(error.to_string())),
+        }
+    }
+
+    /// Ask Git for the entries of the tree at a revision (e.g. `HEAD` or `HEAD:src`), optionally
+    /// including the contents of subtrees instead of the subtrees themselves.
+    pub fn ls_tree(
+        rev: &str,
+        recursive: bool,
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<TreeEntry>, GitCommandError> {
+        let mut args = vec!["-z"];
+        if recursive {
+            args.push("-r");
+        }
+        args.push(rev);
+        let output = GitCommand::git_command::<String>(
+            "ls-tree",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(TreeEntry::from_ls_tree_z(&output))
+    }
+
+    /// Ask Git to make a tree object from a list of entries and return its OID.
+    /// The entries' objects must already exist, e.g. blobs written by `git hash-object -w`,
+    /// and their paths must be simple names, not paths with slashes.
+    pub fn mktree(
+        entries: &[TreeEntry],
+        path: Option<&PathBuf>,
+    ) -> Result<String, GitCommandError> {
+        let mut lines = vec![];
+        for entry in entries {
+            if !entry.has_valid_mode() {
+                return Err(GitCommandError::new(format!(
+                    "Invalid mode {} for {} {}",
+                    entry.mode(),
+                    entry.kind(),
+                    entry.path().display()
+                )));
+            }
+            let name = Self::path_str(entry.path())?;
+            if name.is_empty() || name.contains(['/', '\n', '\0']) {
+                return Err(GitCommandError::new(format!(
+                    "Invalid tree entry name {name:?}"
+                )));
+            }
+            lines.push(format!(
+                "{} {} {}\t{}",
+                entry.mode(),
+                entry.kind(),
+                entry.oid(),
+                name
+            ));
+        }
+        // The payload gets a newline after it, which mktree doesn't accept on its own,
+        // so an empty tree gets no payload at all
+        let payload = if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        };
+        let output =
+            GitCommand::git_command("mktree", None, payload, Some(&Self::path(path.cloned())))?;
+        Ok(output.trim_end_matches('\n').to_owned())
+    }
+
+    /// Ask Git for the OIDs of the objects that have been replaced by other objects.
+    pub fn replace_list(path: Option<&PathBuf>) -> Result<Vec<String>, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "replace",
+            Some(vec!["--list", "--format=short"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(output.lines().map(|line| line.to_owned()).collect())
+    }
+
+    /// Make Git use a replacement object wherever an object is referred to, without rewriting
+    /// the history that refers to it.
+    pub fn replace_add(
+        object: &str,
+        replacement: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let _ = GitCommand::git_command::<String>(
+            "replace",
+            Some(vec![object, replacement]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Stop replacing an object.
+    pub fn replace_remove(object: &str, path: Option<&PathBuf>) -> Result<(), GitCommandError> {
+        let _ = GitCommand::git_command::<String>(
+            "replace",
+            Some(vec!["--delete", object]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Ask Git to delete loose objects that can't be reached from any ref, and return their OIDs.
+    /// With `dry_run`, nothing is deleted, and the objects that would be are returned.
+    pub fn prune(dry_run: bool, path: Option<&PathBuf>) -> Result<Vec<String>, GitCommandError> {
+        let mut args = vec!["--verbose"];
+        if dry_run {
+            args.push("--dry-run");
+        }
+        let output = GitCommand::git_command::<String>(
+            "prune",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // Each line is the object's OID followed by its type
+        Ok(output
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .map(|oid| oid.to_owned())
+            .collect())
+    }
+
+    /// Ask Git to pack loose objects. With `all`, everything is packed into a single pack, and
+    /// with `remove_redundant`, packs and loose objects made redundant by the new pack are deleted.
+    pub fn repack(
+        all: bool,
+        remove_redundant: bool,
+        path: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let mut args = vec!["-q"];
+        if all {
+            args.push("-a");
+        }
+        if remove_redundant {
+            args.push("-d");
+        }
+        let _ = GitCommand::git_command::<String>(
+            "repack",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Ask Git to verify a pack file, given its index (`.idx`) file, and return statistics about
+    /// the objects in it.
+    pub fn verify_pack(idx: &Path, path: Option<&PathBuf>) -> Result<PackStats, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "verify-pack",
+            Some(vec!["--verbose", Self::path_str(idx)?]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(PackStats::from_verify_pack(&output))
+    }
+
+    /// Ask Git for the message of an annotated tag.
+    /// Returns `None` for a lightweight tag, which doesn't have a message of its own.
+    pub fn tag_message(
+        name: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<Option<String>, GitCommandError> {
+        let tag_ref = format!("refs/tags/{name}");
+        let output = GitCommand::git_command::<String>(
+            "for-each-ref",
+            Some(vec!["--format=%(objecttype)%00%(contents)", &tag_ref]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        match output.split_once('\0') {
+            // Only annotated tags are tag objects; lightweight tags point straight at a commit
+            Some(("tag", contents)) => {
+                // Git adds a newline after the format
+                let contents = contents.strip_suffix('\n').unwrap_or(contents);
+                Ok(Some(contents.to_owned()))
+            }
+            Some(_) => Ok(None),
+            None => Err(GitCommandError::new(format!("No such tag: {name}"))),
+        }
+    }
+
+    /// Ask Git for everything about a tag: its target, and for an annotated tag,
+    /// its tagger and message.
+    pub fn tag_info(name: &str, path: Option<&PathBuf>) -> Result<TagInfo, GitCommandError> {
+        let tag_ref = format!("refs/tags/{name}");
+        let output = GitCommand::git_command::<String>(
+            "for-each-ref",
+            Some(vec![TagInfo::FORMAT, &tag_ref]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        match TagInfo::from_for_each_ref(&output) {
+            Some(tag_info) => Ok(tag_info),
+            None => Err(GitCommandError::new(format!("No such tag: {name}"))),
+        }
+    }
+
+    /// Ask Git for the branches that contain a commit, optionally including remote-tracking
+    /// branches as well as local ones.
+    pub fn branches_containing(
+        commit: &str,
+        remote: bool,
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<String>, GitCommandError> {
+        let mut args = vec!["--format=%(refname:short)", "--contains", commit];
+        if remote {
+            args.push("--all");
+        }
+        let output = GitCommand::git_command::<String>(
+            "branch",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(output
+            .lines()
+            // A detached HEAD is listed as e.g. "(HEAD detached at 1234567)"
+            .filter(|line| !line.is_empty() && !line.starts_with('('))
+            .map(|line| line.to_owned())
+            .collect())
+    }
+
+    /// Ask Git for the tags that contain a commit, e.g. to find which releases include a fix.
+    pub fn tags_containing(
+        commit: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<String>, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "tag",
+            Some(vec!["--list", "--contains", commit]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(output.lines().map(|line| line.to_owned()).collect())
+    }
+
+    /// Ask Git for the word-by-word differences between two revisions, optionally limited to
+    /// one file, in Git's machine-readable porcelain word diff format: each changed word is on
+    /// a line of its own starting with `-` or `+`, unchanged text starts with a space, and `~`
+    /// marks the end of a line.
+    pub fn diff_words(
+        from: &str,
+        to: &str,
+        file: Option<&str>,
+        path: Option<&PathBuf>,
+    ) -> Result<String, GitCommandError> {
+        let mut args = vec!["--word-diff=porcelain", from, to, "--"];
+        if let Some(file) = file {
+            args.push(file);
+        }
+        GitCommand::git_command::<String>(
+            "diff",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )
+    }
+
+    /// Ask Git for a summary of the changes in a range (e.g. `main..feature` or `HEAD~2`),
+    /// as the number of files changed, lines inserted and lines deleted.
+    pub fn shortstat(
+        range: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<(u32, u32, u32), GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "diff",
+            Some(vec!["--shortstat", range, "--"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // e.g. " 2 files changed, 3 insertions(+), 1 deletion(-)", where parts with a count
+        // of zero are left out, and there's no output at all if nothing changed
+        let mut stats = (0, 0, 0);
+        for part in output.trim().split(", ").filter(|part| !part.is_empty()) {
+            let Some((count, description)) = part.split_once(' ') else {
+                continue;
+            };
+            let count = match count.parse::<u32>() {
+                Ok(count) => count,
+                Err(error) => return Err(GitCommandError::new(error.to_string())),
+            };
+            if description.starts_with("file") {
+                stats.0 = count;
+            } else if description.starts_with("insertion") {
+                stats.1 = count;
+            } else if description.starts_with("deletion") {
+                stats.2 = count;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Ask Git who last changed each line of a file in the working tree.
+    pub fn blame(file: &str, path: Option<&PathBuf>) -> Result<Vec<BlameLine>, GitCommandError> {
+        Self::blame_lines(file, None, path)
+    }
+
+    /// Ask Git who last changed each line of a file as it was at a revision, e.g. a tag.
+    pub fn blame_at(
+        file: &str,
+        rev: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<BlameLine>, GitCommandError> {
+        Self::blame_lines(file, Some(rev), path)
+    }
+
+    /// Ask Git who last changed each line of a file, in Git's porcelain format, for callers
+    /// that parse it themselves.
+    pub fn blame_raw(file: &str, path: Option<&PathBuf>) -> Result<String, GitCommandError> {
+        GitCommand::git_command::<String>(
+            "blame",
+            Some(vec!["--porcelain", "--", file]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )
+    }
+
+    /// Ask Git for the base, our and their versions of a conflicted file.
+    pub fn conflict_versions(
+        file: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<ConflictVersions, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "ls-files",
+            Some(vec!["--unmerged", "-z", "--", file]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        let stages = ConflictVersions::stages_from_ls_files_z(&output);
+        if stages.is_empty() {
+            return Err(GitCommandError::new(format!("{file} is not conflicted")));
+        }
+        let mut versions = ConflictVersions::default();
+        for stage in stages {
+            // `./` makes the path relative to the current directory, like ls-files, rather than
+            // to the top level
+            let contents = Self::show_bytes(&format!(":{stage}:./{file}"), path)?;
+            versions.set_stage(stage, contents);
+        }
+        Ok(versions)
+    }
+
+    /// Replace a conflicted file with its resolved contents and tell Git it's resolved.
+    pub fn resolve_conflict(
+        file: &str,
+        content: &[u8],
+        path: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let dir = Self::path(path.cloned());
+        let output = GitCommand::git_command::<String>(
+            "ls-files",
+            Some(vec!["--unmerged", "--", file]),
+            None,
+            Some(&dir),
+        )?;
+        if output.is_empty() {
+            return Err(GitCommandError::new(format!("{file} is not conflicted")));
+        }
+        if let Err(error) = std::fs::write(dir.join(file), content) {
+            return Err(GitCommandError::new(error.to_string()));
+        }
+        let _ = GitCommand::git_command::<String>("add", Some(vec!["--", file]), None, Some(&dir))?;
+        Ok(())
+    }
+
+    /// Ask Git for the commit message it has prepared for a merge that stopped because of
+    /// conflicts, or `None` if there isn't one.
+    pub fn merge_message(path: Option<&PathBuf>) -> Result<Option<String>, GitCommandError> {
+        let git_dir = Self::git_dir(path)?;
+        // Git also writes MERGE_MSG for a conflicted cherry-pick or revert, and can leave it
+        // behind afterwards, so it only belongs to a merge while MERGE_HEAD exists
+        if !git_dir.join("MERGE_HEAD").is_file() {
+            return Ok(None);
+        }
+        let merge_msg = git_dir.join("MERGE_MSG");
+        if !merge_msg.is_file() {
+            return Ok(None);
+        }
+        match std::fs::read_to_string(merge_msg) {
+            Ok(message) => Ok(Some(message)),
+            Err(error) => Err(GitCommandError::new(error.to_string())),
+        }
+    }
+
+    /// Ask Git to delete remote-tracking branches whose branches no longer exist on the remote,
+    /// and return their names, e.g. `origin/feature`.
+    /// With `dry_run`, nothing is deleted, and the branches that would be are returned.
+    pub fn remote_prune(
+        remote: &str,
+        dry_run: bool,
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<String>, GitCommandError> {
+        let mut args = vec!["prune"];
+        if dry_run {
+            args.push("--dry-run");
+        }
+        args.push(remote);
+        let output = GitCommand::git_command::<String>(
+            "remote",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // Each pruned branch is reported as ` * [pruned] <name>`, or ` * [would prune] <name>`
+        Ok(output
+            .lines()
+            .filter_map(|line| line.trim_start().strip_prefix("* ["))
+            .filter_map(|line| line.split_once("] "))
+            .map(|(_, name)| name.to_owned())
+            .collect())
+    }
+
+    /// Ask Git if a repository is a shallow clone, i.e. it's missing some of its history.
+    pub fn is_shallow(path: Option<&PathBuf>) -> Result<bool, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "rev-parse",
+            Some(vec!["--is-shallow-repository"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        match output.trim_end_matches('\n') {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            other => Err(GitCommandError::new(format!(
+                "Unexpected output from rev-parse: {other}"
+            ))),
+        }
+    }
+
+    /// Apply a stash entry to the working tree, keeping it in the stash list.
+    /// With `restore_index`, changes that were staged when the stash was made are staged again.
+    /// Uses the latest stash entry if no index is given. Conflicts are reported in the outcome
+    /// rather than as an error.
+    pub fn stash_apply(
+        index: Option<usize>,
+        restore_index: bool,
+        path: Option<&PathBuf>,
+    ) -> Result<StashOutcome, GitCommandError> {
+        Self::stash_restore("apply", index, restore_index, path)
+    }
+
+    /// Apply a stash entry to the working tree and remove it from the stash list.
+    /// With `restore_index`, changes that were staged when the stash was made are staged again.
+    /// Uses the latest stash entry if no index is given. Conflicts are reported in the outcome
+    /// rather than as an error.
+    pub fn stash_pop(
+        index: Option<usize>,
+        restore_index: bool,
+        path: Option<&PathBuf>,
+    ) -> Result<StashOutcome, GitCommandError> {
+        Self::stash_restore("pop", index, restore_index, path)
+    }
+
+    /// Repair the links between a repository and its linked working trees after either has been
+    /// moved by something other than `worktree_move`.
+    /// Moved working trees that the repository can't find must be given in `paths`.
+    pub fn worktree_repair(
+        paths: &[&PathBuf],
+        repo: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let mut args = vec!["repair"];
+        for path in paths {
+            args.push(Self::path_str(path)?);
+        }
+        let _ = GitCommand::git_command::<String>(
+            "worktree",
+            Some(args),
+            None,
+            Some(&Self::path(repo.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Ask Git for the folder it runs hooks from, which is `core.hooksPath` if that's set.
+    pub fn hooks_path(path: Option<&PathBuf>) -> Result<PathBuf, GitCommandError> {
+        let dir = Self::path(path.cloned());
+        let output = GitCommand::git_command::<String>(
+            "rev-parse",
+            Some(vec!["--git-path", "hooks"]),
+            None,
+            Some(&dir),
+        )?;
+        // The path is relative to the current directory, unless it was configured as absolute
+        Ok(dir.join(output.trim_end_matches('\n')))
+    }
+
+    /// List the names of the hooks that Git will run, i.e. the executable files in the hooks
+    /// folder, not counting the `.sample` files Git installs.
+    pub fn list_hooks(path: Option<&PathBuf>) -> Result<Vec<String>, GitCommandError> {
+        let hooks_path = Self::hooks_path(path)?;
+        if !hooks_path.is_dir() {
+            return Ok(vec![]);
+        }
+        let entries = match std::fs::read_dir(hooks_path) {
+            Ok(entries) => entries,
+            Err(error) => return Err(GitCommandError::new(error.to_string())),
+        };
+        let mut hooks = vec![];
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.ends_with(".sample") {
+                continue;
+            }
+            if let Ok(metadata) = entry.metadata() {
+                if metadata.is_file() && Self::is_executable(&metadata) {
+                    hooks.push(name);
+                }
+            }
+        }
+        hooks.sort();
+        Ok(hooks)
+    }
+
+    /// Ask Git for the merge tools that are available on this system, e.g. `vimdiff`.
+    pub fn list_merge_tools(path: Option<&PathBuf>) -> Result<Vec<String>, GitCommandError> {
+        Self::list_tools("mergetool", path)
+    }
+
+    /// Ask Git for the diff tools that are available on this system, e.g. `vimdiff`.
+    pub fn list_diff_tools(path: Option<&PathBuf>) -> Result<Vec<String>, GitCommandError> {
+        Self::list_tools("difftool", path)
+    }
+
+    /// Ask Git how many commits can be reached from `to` but not from `from`, e.g. how many
+    /// commits a branch is ahead of `main`.
+    pub fn count_between(
+        from: &str,
+        to: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<u32, GitCommandError> {
+        let range = format!("{from}..{to}");
+        let output = GitCommand::git_command::<String>(
+            "rev-list",
+            Some(vec!["--count", range.as_str(), "--"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        match output.trim_end_matches('\n').parse() {
+            Ok(count) => Ok(count),
+            Err(error) => Err(GitCommandError::new(error.to_string())),
+        }
+    }
+
+    /// Ask Git for the OID of the empty tree, which depends on the repository's hash algorithm.
+    /// It's useful as the "before" side when diffing a root commit.
+    pub fn empty_tree_oid(path: Option<&PathBuf>) -> Result<String, GitCommandError> {
+        // Hash nothing as a tree, without writing it to the repository. Git knows the empty tree
+        // whether or not it's been written, so it can still be used right away.
+        let output = GitCommand::git_command::<String>(
+            "hash-object",
+            Some(vec!["-t", "tree", "--stdin"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(output.trim_end_matches('\n').to_owned())
+    }
+
+    /// Ask Git for the path of the configuration file it would change for a scope.
+    /// The file might not exist yet.
+    pub fn config_path(
+        scope: ConfigScope,
+        path: Option<&PathBuf>,
+    ) -> Result<PathBuf, GitCommandError> {
+        Self::config_path_with(&GitConfig::new(), scope, path)
+    }
+
+    /// Ask Git for the path of the configuration file it would change for a scope, when run with
+    /// the given settings, e.g. with `GIT_CONFIG_GLOBAL` set in its environment.
+    /// The file might not exist yet.
+    pub fn config_path_with(
+        config: &GitConfig,
+        scope: ConfigScope,
+        path: Option<&PathBuf>,
+    ) -> Result<PathBuf, GitCommandError> {
+        let dir = Self::path(path.cloned());
+        match scope {
+            ConfigScope::Local => {
+                let output = GitCommand::git_command_with::<String>(
+                    config,
+                    "rev-parse",
+                    Some(vec!["--git-path", "config"]),
+                    None,
+                    Some(&dir),
+                )?;
+                Ok(dir.join(output.trim_end_matches('\n')))
+            }
+            ConfigScope::Global | ConfigScope::System => {
+                // Git's messages are translated, and the one naming a missing file is parsed
+                let config = config.clone().with_env("LC_ALL", "C");
+                let output = GitCommand::run_git_command_with::<String>(
+                    &config,
+                    "config",
+                    Some(vec![scope.arg(), "--list", "--show-origin", "-z"]),
+                    None,
+                    Some(&dir),
+                )?;
+                if !output.status.success() {
+                    // If the file doesn't exist, Git says "unable to read config file '<path>': ..."
+                    let error = GitCommand::stderr_error(&output);
+                    return match error
+                        .message()
+                        .split_once("unable to read config file '")
+                        .and_then(|(_, rest)| rest.rsplit_once("': "))
+                    {
+                        Some((file, _)) => Ok(PathBuf::from(file)),
+                        None => Err(error),
+                    };
+                }
+                // Each entry starts with its origin, e.g. `file:/etc/gitconfig`, and the first
+                // one is from the file itself, even if it includes others
+                let stdout = GitCommand::stdout(&output)?;
+                if let Some(file) = stdout
+                    .split('\0')
+                    .next()
+                    .and_then(|origin| origin.strip_prefix("file:"))
+                {
+                    return Ok(PathBuf::from(file));
+                }
+                // The file exists but has no settings, so have Git name the file it would edit.
+                // Git only creates a file to edit when there isn't one.
+                let output = GitCommand::git_command_with::<String>(
+                    &config.with_env("GIT_EDITOR", "echo"),
+                    "config",
+                    Some(vec![scope.arg(), "--edit"]),
+                    None,
+                    Some(&dir),
+                )?;
+                Ok(PathBuf::from(output.trim_end_matches('\n')))
+            }
+        }
+    }
+
+    /// Ask Git for the OID of the single object a revision names, or `None` if it doesn't name
+    /// one.
+    pub fn rev_parse_verify(
+        rev: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<Option<String>, GitCommandError> {
+        let output = GitCommand::run_git_command::<String>(
+            "rev-parse",
+            Some(vec!["--verify", "--quiet", rev]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // --quiet exits with 1 and no message if the revision isn't valid
+        match output.status.code() {
+            Some(0) => Ok(Some(
+                GitCommand::stdout(&output)?
+                    .trim_end_matches('\n')
+                    .to_owned(),
+            )),
+            Some(1) if output.stderr.is_empty() => Ok(None),
+            _ => Err(GitCommand::stderr_error(&output)),
+        }
+    }
+
+    /// Ask Git for the shortest prefix of an OID that's unique in the repository, at least
+    /// `min_len` characters long if that's given.
+    pub fn abbreviate(
+        oid: &str,
+        min_len: Option<u32>,
+        path: Option<&PathBuf>,
+    ) -> Result<String, GitCommandError> {
+        let short = match min_len {
+            Some(min_len) => format!("--short={min_len}"),
+            None => "--short".to_owned(),
+        };
+        let output = GitCommand::git_command::<String>(
+            "rev-parse",
+            Some(vec![short.as_str(), "--verify", oid]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(output.trim_end_matches('\n').to_owned())
+    }
+
+    /// Ask Git for the upstream branch of a local branch, e.g. `origin/main`, or `None` if it
+    /// doesn't have one.
+    pub fn tracking_branch(
+        local: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<Option<String>, GitCommandError> {
+        let branch = format!("refs/heads/{local}");
+        let output = GitCommand::git_command::<String>(
+            "for-each-ref",
+            Some(vec!["--format=%(upstream:short)", branch.as_str()]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // There's a line for the branch, which is empty if it has no upstream,
+        // and no line at all if the branch doesn't exist
+        match output.lines().next() {
+            Some("") => Ok(None),
+            Some(upstream) => Ok(Some(upstream.to_owned())),
+            None => Err(GitCommandError::new(format!("No such branch: {local}"))),
+        }
+    }
+
+    /// Ask Git which files a stash entry changed.
+    pub fn stash_files(
+        index: usize,
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<PathBuf>, GitCommandError> {
+        let stash = Self::stash_ref(index);
+        let output = GitCommand::git_command::<String>(
+            "stash",
+            Some(vec!["show", "--name-only", "-z", stash.as_str()]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(Self::paths_from_z(&output))
+    }
+
+    /// Ask Git for whitespace errors and leftover conflict markers in the unstaged changes.
+    pub fn diff_check(path: Option<&PathBuf>) -> Result<Vec<WhitespaceIssue>, GitCommandError> {
+        let output = GitCommand::run_git_command::<String>(
+            "diff",
+            Some(vec!["--check"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // --check exits with 2 when it finds issues, and anything other than that or 0 is a failure
+        match output.status.code() {
+            Some(0) | Some(2) => Ok(WhitespaceIssue::from_diff_check(&GitCommand::stdout(
+                &output,
+            )?)),
+            _ => Err(GitCommand::stderr_error(&output)),
+        }
+    }
+
+    /// Ask Git which files have staged changes, i.e. what the next commit will include.
+    pub fn staged_files(path: Option<&PathBuf>) -> Result<Vec<PathBuf>, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "diff",
+            Some(vec!["--cached", "--name-only", "-z"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(Self::paths_from_z(&output))
+    }
+
+    /// Ask Git for the files with unresolved conflicts, e.g. after a merge, cherry-pick or revert
+    /// stopped.
+    pub fn unmerged_files(path: Option<&PathBuf>) -> Result<Vec<PathBuf>, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "diff",
+            Some(vec!["--name-only", "--diff-filter=U", "-z"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(Self::paths_from_z(&output))
+    }
+
+    /// Create a linked working tree at a path, checking out `commitish`, or HEAD if that isn't
+    /// given. With `new_branch`, a new branch is created there for it; with `detach`, it's
+    /// checked out without a branch. The two can't be used together.
+    pub fn worktree_add(
+        path: &Path,
+        commitish: Option<&str>,
+        new_branch: Option<&str>,
+        detach: bool,
+        repo: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let mut args = vec!["add"];
+        match (new_branch, detach) {
+            (Some(_), true) => {
+                return Err(GitCommandError::new(
+                    "A worktree can't be given a new branch and be detached".to_owned(),
+                ))
+            }
+            (Some(new_branch), false) => args.extend(["-b", new_branch]),
+            (None, true) => args.push("--detach"),
+            (None, false) => {}
+        }
+        args.push(Self::path_str(path)?);
+        if let Some(commitish) = commitish {
+            args.push(commitish);
+        }
+        let _ = GitCommand::git_command::<String>(
+            "worktree",
+            Some(args),
+            None,
+            Some(&Self::path(repo.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Ask Git for the last value of a configuration key, looking only in one scope if that's
+    /// given. Returns `None` if the key isn't set.
+    pub fn config_get(
+        key: &str,
+        scope: Option<ConfigScope>,
+        path: Option<&PathBuf>,
+    ) -> Result<Option<String>, GitCommandError> {
+        Self::config_get_typed(key, None, scope, path)
+    }
+
+    /// Tell Git to set a configuration key in a scope, replacing its value if it's already set.
+    pub fn config_set(
+        key: &str,
+        value: &str,
+        scope: ConfigScope,
+        path: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let _ = GitCommand::git_command::<String>(
+            "config",
+            Some(vec![scope.arg(), key, value]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Tell Git to set a configuration key in a scope, unless it's already set there.
+    /// Returns whether the key was set.
+    pub fn config_set_if_absent(
+        key: &str,
+        value: &str,
+        scope: ConfigScope,
+        path: Option<&PathBuf>,
+    ) -> Result<bool, GitCommandError> {
+        if Self::config_get(key, Some(scope), path)?.is_some() {
+            return Ok(false);
+        }
+        Self::config_set(key, value, scope, path)?;
+        Ok(true)
+    }
+
+    /// Ask Git how many commits each author has made, as `(name, email, count)`, with the most
+    /// prolific author first.
+    /// Fails with `ErrorKind::NoCommits` if the current branch doesn't have any commits yet.
+    pub fn commit_count_by_author(
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<(String, String, u32)>, GitCommandError> {
+        let head = Self::head_sha(path)?;
+        let output = GitCommand::git_command::<String>(
+            "log",
+            Some(vec!["--format=%an%x00%ae", &head, "--"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        let mut authors: HashMap<(&str, &str), u32> = HashMap::new();
+        for line in output.lines() {
+            *authors
+                .entry(line.split_once('\0').unwrap_or((line, "")))
+                .or_default() += 1;
+        }
+        let mut counts: Vec<(String, String, u32)> = authors
+            .into_iter()
+            .map(|((name, email), count)| (name.to_owned(), email.to_owned(), count))
+            .collect();
+        counts.sort_by(|a, b| {
+            b.2.cmp(&a.2)
+                .then_with(|| a.0.cmp(&b.0))
+                .then_with(|| a.1.cmp(&b.1))
+        });
+        Ok(counts)
+    }
+
+    /// Ask Git for the mode of a tracked file in the index, e.g. `100644` for a regular file,
+    /// `100755` for an executable file or `120000` for a symbolic link.
+    pub fn file_mode(file: &str, path: Option<&PathBuf>) -> Result<String, GitCommandError> {
+        // The path is taken literally, not as a pattern, but it still matches the files in a
+        // folder, so only an entry for the path itself counts
+        let pathspec = format!(":(literal){file}");
+        let output = GitCommand::git_command::<String>(
+            "ls-files",
+            Some(vec!["--stage", "-z", "--error-unmatch", "--", &pathspec]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // Each entry is `<mode> <oid> <stage>\t<path>`
+        let mode = output
+            .split('\0')
+            .filter_map(|entry| entry.split_once('\t'))
+            .find(|(_, entry_path)| *entry_path == file)
+            .and_then(|(info, _)| info.split(' ').next());
+        match mode {
+            Some(mode) => Ok(mode.to_owned()),
+            None => Err(GitCommandError::new(format!(
+                "{file} is not a tracked file"
+            ))),
+        }
+    }
+
+    /// Tell Git to record a tracked file as executable or not in the index, regardless of what
+    /// the file system says, e.g. on Windows.
+    pub fn set_executable(
+        file: &str,
+        executable: bool,
+        path: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let chmod = if executable {
+            "--chmod=+x"
+        } else {
+            "--chmod=-x"
+        };
+        let _ = GitCommand::git_command::<String>(
+            "update-index",
+            Some(vec![chmod, "--", file]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(())
+    }
+
+    /// Ask Git for the total size in bytes of the files tracked at HEAD.
+    pub fn tracked_size(path: Option<&PathBuf>) -> Result<u64, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "ls-tree",
+            Some(vec!["-r", "-l", "-z", "HEAD"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // Each record is `<mode> <type> <oid> <size>\t<path>`, where the size is right-aligned,
+        // and is `-` for submodules
+        let mut total = 0;
+        for record in output.split('\0') {
+            let size = record
+                .split_once('\t')
+                .and_then(|(info, _)| info.split_whitespace().nth(3));
+            if let Some(size) = size.and_then(|size| size.parse::<u64>().ok()) {
+                total += size;
+            }
+        }
+        Ok(total)
+    }
+
+    /// Ask Git if it treats a file as binary, either as it is at a revision or, if no revision is
+    /// given, as it is in the working tree. This takes `.gitattributes` into account.
+    pub fn is_binary(
+        file: &str,
+        rev: Option<&str>,
+        path: Option<&PathBuf>,
+    ) -> Result<bool, GitCommandError> {
+        // Diff the file against the empty tree, so that all of it is counted as added
+        let empty_tree = Self::empty_tree_oid(path)?;
+        let mut args = vec!["--numstat", empty_tree.as_str()];
+        if let Some(rev) = rev {
+            args.push(rev);
+        }
+        args.extend(["--", file]);
+        let output = GitCommand::git_command::<String>(
+            "diff",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // Each line is `<added>\t<deleted>\t<path>`, where the counts are `-` for a binary file
+        match output.lines().next() {
+            Some(line) => Ok(line.starts_with("-\t-\t")),
+            None => Err(GitCommandError::new(format!("{file} is not tracked"))),
+        }
+    }
+
+    /// Ask Git for the refs of a kind, e.g. the local branches, and what they point to.
+    pub fn refs(kind: RefKind, path: Option<&PathBuf>) -> Result<Vec<Ref>, GitCommandError> {
+        let mut args = vec![Ref::FORMAT];
+        if let Some(pattern) = kind.pattern() {
+            args.push(pattern);
+        }
+        let output = GitCommand::git_command::<String>(
+            "for-each-ref",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(Ref::from_for_each_ref(&output))
+    }
+
+    /// Ask Git which files changed between two commits, and how.
+    /// Without `to`, `from` is compared to the working tree, without `from`, HEAD is compared to
+    /// `to`, and without either, the index is compared to the working tree.
+    /// A change Git reports with a status this library doesn't model is returned as an error.
+    pub fn diff_name_status(
+        from: Option<&str>,
+        to: Option<&str>,
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<(ChangeKind, PathBuf)>, GitCommandError> {
+        let mut args = vec!["--name-status", "-M", "-z"];
+        match (from, to) {
+            (None, Some(to)) => args.extend(["HEAD", to]),
+            (from, to) => {
+                args.extend(from);
+                args.extend(to);
+            }
+        }
+        args.push("--");
+        let output = GitCommand::git_command::<String>(
+            "diff",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        StatusEntry::from_name_status_z(&output)
+            .into_iter()
+            .map(|entry| match ChangeKind::from_status_entry(&entry) {
+                Some(kind) => Ok((kind, entry.path().clone())),
+                None => Err(GitCommandError::new(format!(
+                    "Unexpected status {} for {}",
+                    entry.status(),
+                    entry.path().display()
+                ))),
+            })
+            .collect()
+    }
+
+    /// Ask Git for the state of each submodule, including nested ones.
+    pub fn submodule_status(
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<SubmoduleStatus>, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "submodule",
+            Some(vec!["status", "--recursive"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(SubmoduleStatus::from_submodule_status(&output))
+    }
+
+    /// Run a command in each submodule, and in nested submodules with `recursive`, and return
+    /// its output, along with the `Entering '<path>'` lines Git prints before each run.
+    /// The command and its arguments are passed to the program as they are: each one is quoted
+    /// before Git hands the command to the shell, so none of them are expanded or interpreted.
+    pub fn submodule_foreach(
+        command: &[&str],
+        recursive: bool,
+        path: Option<&PathBuf>,
+    ) -> Result<String, GitCommandError> {
+        if command.is_empty() {
+            return Err(GitCommandError::new("No command to run".to_owned()));
+        }
+        let mut args = vec!["foreach"];
+        if recursive {
+            args.push("--recursive");
+        }
+        // Git runs the command with the shell, so quote every element so it's taken literally
+        let command = command
+            .iter()
+            .map(|arg| format!("'{}'", arg.replace('\'', "'\\''")))
+            .collect::<Vec<_>>()
+            .join(" ");
+        args.push(&command);
+        GitCommand::git_command::<String>(
+            "submodule",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )
+    }
+
+    /// Ask Git for the branches it knows about on a remote, e.g. `main` for `origin/main`,
+    /// as of the last fetch.
+    pub fn remote_branches(
+        remote: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<String>, GitCommandError> {
+        let prefix = format!("refs/remotes/{remote}/");
+        let output = GitCommand::git_command::<String>(
+            "for-each-ref",
+            Some(vec!["--format=%(refname)", prefix.as_str()]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(output
+            .lines()
+            .filter_map(|name| name.strip_prefix(prefix.as_str()))
+            // HEAD is a symbolic ref to the remote's default branch, not a branch of its own
+            .filter(|name| *name != "HEAD")
+            .map(|name| name.to_owned())
+            .collect())
+    }
+
+    /// Ask Git which configuration files it reads because another configuration file includes
+    /// them with `include.path` or `includeIf`. Files that don't set anything aren't reported.
+    pub fn config_includes(path: Option<&PathBuf>) -> Result<Vec<PathBuf>, GitCommandError> {
+        let dir = Self::path(path.cloned());
+        let origins = |no_includes: bool| -> Result<Vec<PathBuf>, GitCommandError> {
+            let mut args = vec!["--list", "--show-origin", "-z"];
+            if no_includes {
+                args.push("--no-includes");
+            }
+            let output = GitCommand::git_command::<String>("config", Some(args), None, Some(&dir))?;
+            Ok(ConfigEntry::from_list_z(&output)
+                .iter()
+                .filter_map(|entry| entry.origin_path())
+                .collect())
+        };
+        let primary = origins(true)?;
+        let mut includes: Vec<PathBuf> = vec![];
+        for origin in origins(false)? {
+            if !primary.contains(&origin) && !includes.contains(&origin) {
+                includes.push(origin);
+            }
+        }
+        // Files in the repository are reported relative to the directory Git was run in
+        Ok(includes.into_iter().map(|file| dir.join(file)).collect())
+    }
+
+    /// Ask Git to verify the GPG signature of every tag, and return each tag's name and
+    /// signature status. Lightweight tags and unsigned annotated tags have no signature.
+    pub fn signed_tags(
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<(String, SignatureStatus)>, GitCommandError> {
+        let dir = Self::path(path.cloned());
+        let output = GitCommand::git_command::<String>(
+            "for-each-ref",
+            Some(vec!["--format=%(refname:short)", "refs/tags"]),
+            None,
+            Some(&dir),
+        )?;
+        let mut tags = vec![];
+        for tag in output.lines() {
+            // verify-tag fails for anything other than a good signature, including no signature
+            // at all, so its exit status isn't useful here
+            let output = GitCommand::run_git_command::<String>(
+                "verify-tag",
+                Some(vec!["--raw", tag]),
+                None,
+                Some(&dir),
+            )?;
+            let status = String::from_utf8_lossy(&output.stderr);
+            tags.push((tag.to_owned(), SignatureStatus::from_gpg_status(&status)));
+        }
+        Ok(tags)
+    }
+
+    /// Find the working tree of a repository that contains a path, if any. When working trees
+    /// are nested, the innermost one is returned.
+    pub fn worktree_of(
+        path: &Path,
+        repo: Option<&PathBuf>,
+    ) -> Result<Option<Worktree>, GitCommandError> {
+        // Git reports working tree paths with symbolic links resolved
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        Ok(Self::worktree_list(repo)?
+            .into_iter()
+            .filter(|worktree| path.starts_with(worktree.path()))
+            .max_by_key(|worktree| worktree.path().components().count()))
+    }
+
+    /// Ask Git for the names of a repository's remotes.
+    pub fn remotes(path: Option<&PathBuf>) -> Result<Vec<String>, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "remote",
+            None,
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(output.lines().map(|remote| remote.to_owned()).collect())
+    }
+
+    /// Work out which remote to push to or pull from by default: the current branch's configured
+    /// remote, or the only remote if the branch doesn't have one. Returns `None` if there's no
+    /// remote, or more than one to choose from.
+    pub fn default_remote(path: Option<&PathBuf>) -> Result<Option<String>, GitCommandError> {
+        let output = GitCommand::run_git_command::<String>(
+            "symbolic-ref",
+            Some(vec!["--quiet", "--short", "HEAD"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // A detached HEAD isn't on a branch, so it can't have a configured remote
+        if output.status.success() {
+            let branch = GitCommand::stdout(&output)?;
+            let key = format!("branch.{}.remote", branch.trim_end_matches('\n'));
+            if let Some(remote) = Self::config_get(&key, None, path)? {
+                return Ok(Some(remote));
+            }
+        }
+        let mut remotes = Self::remotes(path)?;
+        if remotes.len() == 1 {
+            Ok(remotes.pop())
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Ask Git for the untracked files it's ignoring because of `.gitignore` and other ignore
+    /// rules. A directory whose contents are all ignored is listed by itself, with a trailing
+    /// slash.
+    pub fn ignored_files(path: Option<&PathBuf>) -> Result<Vec<PathBuf>, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            "status",
+            Some(vec!["--porcelain", "--ignored", "-z"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // Each entry is a two-character status, a space and a path, where renames and copies
+        // are followed by an extra entry with the original path
+        let mut ignored = vec![];
+        let mut entries = output.split('\0').filter(|entry| !entry.is_empty());
+        while let Some(entry) = entries.next() {
+            if let Some(file) = entry.strip_prefix("!! ") {
+                ignored.push(PathBuf::from(file));
+            } else if entry.starts_with(['R', 'C']) {
+                entries.next();
+            }
+        }
+        Ok(ignored)
+    }
+
+    /// Fetch a single ref from a remote into a local ref, e.g. a pull request's
+    /// `refs/pull/42/head` into `refs/heads/pr-42`.
+    pub fn fetch_ref(
+        remote: &str,
+        remote_ref: &str,
+        local_ref: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<(), GitCommandError> {
+        let refspec = format!("{remote_ref}:{local_ref}");
+        let output = GitCommand::run_git_command::<String>(
+            "fetch",
+            Some(vec!["--quiet", remote, refspec.as_str()]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let error = GitCommand::stderr_error(&output);
+        if error.message().contains("couldn't find remote ref") {
+            Err(GitCommandError::new(format!(
+                "{remote} doesn't have a ref named {remote_ref}"
+            )))
+        } else {
+            Err(error)
+        }
+    }
+
+    /// Ask Git for the contents of a file as it was at a revision.
+    /// The file's path is relative to the top level of the repository.
+    pub fn show_file_at_rev(
+        rev: &str,
+        file: &str,
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<u8>, GitCommandError> {
+        Self::show_bytes(&format!("{rev}:{file}"), path)
+    }
+
+    /// Write the contents of a file as it was at a revision to a writer, without holding it all
+    /// in memory, and return the number of bytes written.
+    /// The file's path is relative to the top level of the repository.
+    pub fn show_file_at_rev_to_writer(
+        rev: &str,
+        file: &str,
+        writer: &mut dyn Write,
+        path: Option<&PathBuf>,
+    ) -> Result<u64, GitCommandError> {
+        let object = format!("{rev}:{file}");
+        GitCommand::git_command_to_writer(
+            "show",
+            Some(vec![object.as_str()]),
+            writer,
+            Some(&Self::path(path.cloned())),
+        )
+    }
+
+    /// Ask Git for the top level folder of a path.
+    /// Ask Git to fill in the username and password for the given url and return the full credentials.
+    pub fn credentials_fill(url: &str) -> Result<Credentials, GitCommandError> {
+        Self::credentials_fill_with(&GitConfig::new(), &[], url)
+    }
+
+    /// Ask Git to fill in the username and password for the given url and return the full
+    /// credentials, failing instead of prompting for them if no credential helper has them.
+    /// Git's terminal prompt and askpass programs are disabled, and credential helpers are asked
+    /// not to interact with the user (which Git 2.46 and later support). In case a helper waits
+    /// for input anyway, the command is killed after the configured timeout, or 30 seconds.
+    pub fn credentials_fill_nonblocking(url: &str) -> Result<Credentials, GitCommandError> {
+        let timeout = GitCommand::config()
+            .timeout()
+            .unwrap_or(Duration::from_secs(30));
+        let config = GitConfig::new()
+            .with_env("GIT_TERMINAL_PROMPT", "0")
+            .with_env("GIT_ASKPASS", "")
+            .with_env("SSH_ASKPASS", "")
+            .with_timeout(timeout);
+        Self::credentials_fill_with(&config, &["credential.interactive=never"], url)
+    }
+
+    /// Tell Git that the given credentials were accepted by an operation.
+    pub fn credentials_approve(credentials: &Credentials) -> Result<(), GitCommandError> {
+        let _ =
+            GitCommand::git_command("credential", Some(vec!["approve"]), Some(credentials), None)?;
+        Ok(())
+    }
+
+    /// Tell Git that the given credentials were rejected by an operation.
+    pub fn credentials_reject(credentials: &Credentials) -> Result<(), GitCommandError> {
+        let _ =
+            GitCommand::git_command("credential", Some(vec!["reject"]), Some(credentials), None)?;
+        Ok(())
+    }
+}
+
+impl GitLib {
+    /// Ask Git who last changed each line of a file, either at a revision or in the working tree
+    fn blame_lines(
+        file: &str,
+        rev: Option<&str>,
+        path: Option<&PathBuf>,
+    ) -> Result<Vec<BlameLine>, GitCommandError> {
+        let mut args = vec!["--line-porcelain"];
+        if let Some(rev) = rev {
+            args.push(rev);
+        }
+        args.extend(["--", file]);
+        let output = GitCommand::git_command::<String>(
+            "blame",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        Ok(BlameLine::from_line_porcelain(&output))
+    }
+
+    /// Splits a record of `git config -z` output into its key and value,
+    /// which are separated by a newline.
+    fn config_key_value(record: &str) -> (String, String) {
+        match record.split_once('\n') {
+            Some((key, value)) => (key.to_owned(), value.to_owned()),
+            None => (record.to_owned(), String::new()),
+        }
+    }
+
+    /// Ask Git for the contents of an object, as bytes, since they might not be text
+    fn show_bytes(object: &str, path: Option<&PathBuf>) -> Result<Vec<u8>, GitCommandError> {
+        let output = GitCommand::run_git_command::<String>(
+            "show",
+            Some(vec![object]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        if output.status.success() {
+            Ok(output.stdout)
+        } else {
+            Err(GitCommand::stderr_error(&output))
+        }
+    }
+
+    /// Runs `git stash apply` or `git stash pop`
+    fn stash_restore(
+        subcommand: &str,
+        index: Option<usize>,
+        restore_index: bool,
+        path: Option<&PathBuf>,
+    ) -> Result<StashOutcome, GitCommandError> {
+        let stash = index.map(Self::stash_ref);
+        let mut args = vec![subcommand];
+        if restore_index {
+            args.push("--index");
+        }
+        if let Some(stash) = &stash {
+            args.push(stash);
+        }
+        let output = GitCommand::run_git_command::<String>(
+            "stash",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        if output.status.success() {
+            return Ok(StashOutcome::Applied);
+        }
+        let conflicts = Self::unmerged_files(path)?;
+        if conflicts.is_empty() {
+            Err(GitCommand::stderr_error(&output))
+        } else {
+            Ok(StashOutcome::Conflict(conflicts))
+        }
+    }
+
+    /// Returns whether a file can be executed. Outside Unix, every file is considered executable.
+    #[cfg(unix)]
+    fn is_executable(metadata: &std::fs::Metadata) -> bool {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o111 != 0
+    }
+
+    #[cfg(not(unix))]
+    fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+        true
+    }
+
+    /// Runs `git mergetool --tool-help` or `git difftool --tool-help` and returns the names of
+    /// the tools it says are available
+    fn list_tools(command: &str, path: Option<&PathBuf>) -> Result<Vec<String>, GitCommandError> {
+        let output = GitCommand::git_command::<String>(
+            command,
+            Some(vec!["--tool-help"]),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // The available tools are listed after "... may be set to one of the following:", one per
+        // indented line, followed by their descriptions, up to a blank line. Tools that Git knows
+        // about but can't find are listed in a separate section.
+        Ok(output
+            .lines()
+            .skip_while(|line| !line.ends_with("may be set to one of the following:"))
+            .skip(1)
+            .take_while(|line| !line.trim().is_empty())
+            .filter_map(|line| line.split_whitespace().next())
+            .map(|tool| tool.to_owned())
+            .collect())
+    }
+
+    /// Runs `git credential fill` with the given settings
+    fn credentials_fill_with(
+        config: &GitConfig,
+        settings: &[&str],
+        url: &str,
+    ) -> Result<Credentials, GitCommandError> {
+        // Settings are given to Git with -c, before the command
+        let mut args = vec![];
+        for setting in settings {
+            args.extend(["-c", setting]);
+        }
+        args.extend(["credential", "fill"]);
+        let credentials = Credentials::with_url(url);
+        let output = GitCommand::git_command_with(
+            config,
+            args[0],
+            Some(args[1..].to_vec()),
+            Some(credentials),
+            None,
+        )?;
+        match Credentials::from_str(output.as_str()) {
+            Ok(credentials) => Ok(credentials),
+            Err(error) => Err(GitCommandError::new(format!(
+                "Failed converting output to credentials: {error}"
+            ))),
+        }
+    }
+
+    /// Splits NUL-separated paths, as output by Git's `-z` option
+    fn paths_from_z(output: &str) -> Vec<PathBuf> {
+        output
+            .split('\0')
+            .filter(|file| !file.is_empty())
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    /// Returns a path as a string, so it can be passed to Git as an argument
+    fn path_str(path: &Path) -> Result<&str, GitCommandError> {
+        match path.to_str() {
+            Some(path) => Ok(path),
+            None => Err(GitCommandError::new(format!(
+                "Path isn't valid UTF-8: {}",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Ask Git for the last value of a configuration key, optionally converted to a type
+    /// (e.g. `bool` or `int`) and only looking in one scope. Returns `None` if the key isn't set.
+    fn config_get_typed(
+        key: &str,
+        value_type: Option<&str>,
+        scope: Option<ConfigScope>,
+        path: Option<&PathBuf>,
+    ) -> Result<Option<String>, GitCommandError> {
+        let value_type = value_type.map(|value_type| format!("--type={value_type}"));
+        let mut args = vec![];
+        if let Some(scope) = scope {
+            args.push(scope.arg());
+        }
+        if let Some(value_type) = &value_type {
+            args.push(value_type);
+        }
+        args.extend(["--get", key]);
+        let output = GitCommand::run_git_command::<String>(
+            "config",
+            Some(args),
+            None,
+            Some(&Self::path(path.cloned())),
+        )?;
+        // --get exits with 1 if the key isn't set
+        match output.status.code() {
+            Some(0) => Ok(Some(
+                GitCommand::stdout(&output)?
+                    .trim_end_matches('\n')
+                    .to_owned(),
+            )),
+            Some(1) => Ok(None),
+            _ => Err(GitCommand::stderr_error(&output)),
+        }
+    }
+
+    /// Returns the name of a stash entry, e.g. `stash@{0}`
+    fn stash_ref(index: usize) -> String {
+        format!("stash@{{{index}}}")
+    }
+
+    fn path(path: Option<PathBuf>) -> PathBuf {
+        if let Some(path) = path {
+            path
+        } else {
+            std::env::current_dir().expect("Error getting cwd")
+        }
+    }
+}
+
+// All of these tests suck....
+#[test]
+// This is not appropriate for automated testing, because it relies on user input.
+// What's more, it requires that the enters specific values.
+fn credential() {
+    let url = "http://example.com";
+    let username = "baravelli";
+    let password = "swordfish";
+
+    // Get the username and password for the url from the user.
+    // The user is expected to enter the values above.
+    let credentials = GitLib::credentials_fill(url).expect("Failed to fill credentials (prompt)");
+
+    // Store the credentials for the url in the credential manager
+    GitLib::credentials_approve(&credentials).expect("Failed to approve credentials");
+
+    // Get the credentials for the url again.
+    // This time they should come from the credential manager.
+    let credentials_fill =
+        GitLib::credentials_fill(url).expect("Failed to fill credentials (approved)");
+
+    // Verify that the username and password are what are expected
+    assert_eq!(
+        credentials_fill.username().as_ref(),
+        Some(&username.to_string())
+    );
+    assert_eq!(
+        credentials_fill.password().as_ref(),
+        Some(&password.to_string())
+    );
+
+    // Remove the credentials for the url from the credential manager
+    GitLib::credentials_reject(&credentials_fill).expect("Failed to reject credentials");
+
+    // If the above worked, the credential manager will prompt the user again.
+    // The user is expected to cancel the dialog.
+    let credentials_fill =
+        GitLib::credentials_fill(url).expect("Failed to fill credentials (rejected)");
+    assert_eq!(credentials_fill.username().as_ref(), Some(&"".to_string()));
+    assert_eq!(credentials_fill.password().as_ref(), Some(&"".to_string()));
+}
+
+#[test]
+fn is_in_work_tree() {
+    assert!(GitLib::is_inside_work_tree(None) // cwd
+        .expect("Not inside work tree"));
+}
+
+#[test]
+fn is_in_work_tree_cwd() {
+    assert!(GitLib::is_inside_work_tree(Some(
+        &std::env::current_dir().expect("Error getting cwd")
+    ))
+    .expect("Not inside work tree"));
+}
+
+#[test]
+fn top_level() {
+    // cargo test --lib top_level -- --show-output
+    let cwd = std::env::current_dir().expect("Error getting cwd");
+    let mut dir = cwd.clone();
+    dir.push("src");
+    let top_level = GitLib::top_level(Some(&dir)).expect("No top level");
+    assert_eq!(cwd, top_level)
+}
+
+#[test]
+fn remote_url() {
+    const REMOTE_NAME: &str = "origin";
+    const TEST_PATH: &str = "C:\\Users\\psobo\\Development\\rust\\ckpath";
+    const REMOTE_URL: &str = "http://marconi/gitea/psobolik/ckpath-rust.git";
+
+    let mut path = PathBuf::new();
+    path.push(TEST_PATH);
+    let remote_url =
+        GitLib::remote_url(REMOTE_NAME, Some(&path)).expect("Error getting remove URL");
+    assert_eq!(remote_url, REMOTE_URL);
+}
+
+#[test]
+#[should_panic]
+fn no_remote_url() {
+    const REMOTE_NAME: &str = "origin";
+    const TEST_PATH: &str = "C:\\Users\\psobo\\Development\\rust";
+
+    match GitLib::remote_url(REMOTE_NAME, Some(&PathBuf::from(TEST_PATH))) {
+        Ok(_) => {}
+        Err(error) => panic!("{:?}", error),
+    }
+}
+
+#[test]
+#[should_panic]
+fn not_is_in_work_tree() {
+    const TEST_PATH: &str = "C:\\Users\\psobo\\Development\\rust";
+
+    assert!(
+        !GitLib::is_inside_work_tree(Some(&PathBuf::from(TEST_PATH)))
+            .expect("Not inside work tree")
+    );
+}
+
+#[test]
+#[should_panic]
+fn no_top_level() {
     const TEST_PATH: &str = "C:\\Users\\psobo\\Development\\rust";
 
-    match GitLib::top_level(Some(&PathBuf::from(TEST_PATH))) {
-        Ok(_) => {}
-        Err(error) => panic!("{:?}", error),
+    match GitLib::top_level(Some(&PathBuf::from(TEST_PATH))) {
+        Ok(_) => {}
+        Err(error) => panic!("{:?}", error),
+    }
+}
+
+#[test]
+fn head_sha() {
+    let repo = test_repo::TestRepo::new();
+    let sha = repo.commit_file("file.txt", "contents\n", "First commit");
+    let head_sha = GitLib::head_sha(Some(repo.path())).expect("Error getting HEAD SHA");
+    assert_eq!(head_sha, sha);
+    assert_eq!(head_sha.len(), 40);
+}
+
+#[test]
+fn head_sha_no_commits() {
+    let repo = test_repo::TestRepo::new();
+    match GitLib::head_sha(Some(repo.path())) {
+        Ok(sha) => panic!("Unexpected SHA {sha}"),
+        Err(error) => assert_eq!(error.kind(), ErrorKind::NoCommits),
+    }
+}
+
+#[test]
+fn is_detached_head() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    assert!(!GitLib::is_detached_head(Some(repo.path())).expect("Error checking HEAD"));
+}
+
+#[test]
+fn is_detached_head_detached() {
+    let repo = test_repo::TestRepo::new();
+    let sha = repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.git(&["checkout", "-q", "--detach", &sha]);
+    assert!(GitLib::is_detached_head(Some(repo.path())).expect("Error checking HEAD"));
+}
+
+#[test]
+fn fetch_all() {
+    let source = test_repo::TestRepo::new();
+    source.commit_file("file.txt", "contents\n", "First commit");
+    let first = test_repo::TestRepo::bare();
+    let second = test_repo::TestRepo::bare();
+    source.git(&["push", "-q", first.path_str(), "main"]);
+    source.git(&["push", "-q", second.path_str(), "main"]);
+
+    let repo = test_repo::TestRepo::new();
+    GitLib::remote_add("first", first.path_str(), Some(repo.path())).expect("Error adding remote");
+    GitLib::remote_add("second", second.path_str(), Some(repo.path()))
+        .expect("Error adding remote");
+    GitLib::fetch_all(false, Some(repo.path())).expect("Error fetching");
+
+    let refs = repo.git(&["for-each-ref", "--format=%(refname)", "refs/remotes"]);
+    assert!(refs.contains("refs/remotes/first/main"));
+    assert!(refs.contains("refs/remotes/second/main"));
+}
+
+#[test]
+fn push_delete_branch() {
+    let remote = test_repo::TestRepo::bare();
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    GitLib::remote_add("origin", remote.path_str(), Some(repo.path()))
+        .expect("Error adding remote");
+    repo.git(&["push", "-q", "origin", "main", "main:feature"]);
+    assert!(repo
+        .git(&["ls-remote", "origin"])
+        .contains("refs/heads/feature"));
+
+    GitLib::push_delete_branch("origin", "feature", Some(repo.path()))
+        .expect("Error deleting branch");
+    let refs = repo.git(&["ls-remote", "origin"]);
+    assert!(!refs.contains("refs/heads/feature"));
+    assert!(refs.contains("refs/heads/main"));
+}
+
+#[test]
+fn push_delete_missing_branch() {
+    let remote = test_repo::TestRepo::bare();
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    GitLib::remote_add("origin", remote.path_str(), Some(repo.path()))
+        .expect("Error adding remote");
+    assert!(GitLib::push_delete_branch("origin", "no-such-branch", Some(repo.path())).is_err());
+}
+
+#[test]
+fn push_force_with_lease() {
+    let remote = test_repo::TestRepo::bare();
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    GitLib::remote_add("origin", remote.path_str(), Some(repo.path()))
+        .expect("Error adding remote");
+    GitLib::push("origin", "main", ForceMode::No, Some(repo.path())).expect("Error pushing");
+    repo.git(&["fetch", "-q", "origin"]);
+
+    // Rewrite the commit that was pushed
+    repo.git(&["commit", "-q", "--amend", "-m", "Rewritten commit"]);
+    assert!(GitLib::push("origin", "main", ForceMode::No, Some(repo.path())).is_err());
+    GitLib::push("origin", "main", ForceMode::WithLease, Some(repo.path()))
+        .expect("Error force pushing");
+    assert_eq!(remote.git(&["rev-parse", "main"]).trim_end(), repo.head());
+}
+
+#[test]
+fn stash_show() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.write("file.txt", "changed contents\n");
+    repo.git(&["stash", "-q"]);
+
+    let patch = GitLib::stash_show(0, Some(repo.path())).expect("Error showing stash");
+    assert!(patch.contains("file.txt"));
+    assert!(patch.contains("+changed contents"));
+    assert!(GitLib::stash_show(1, Some(repo.path())).is_err());
+}
+
+#[test]
+fn rev_list() {
+    let repo = test_repo::TestRepo::new();
+    let first = repo.commit_file("file.txt", "one\n", "First commit");
+    let second = repo.commit_file("file.txt", "two\n", "Second commit");
+    let third = repo.commit_file("file.txt", "three\n", "Third commit");
+
+    let shas = GitLib::rev_list("HEAD", None, Some(repo.path())).expect("Error listing revisions");
+    assert_eq!(shas, vec![third.clone(), second.clone(), first.clone()]);
+    let shas =
+        GitLib::rev_list("HEAD", Some(2), Some(repo.path())).expect("Error listing revisions");
+    assert_eq!(shas, vec![third.clone(), second]);
+    let range = format!("{first}..HEAD");
+    let shas =
+        GitLib::rev_list(&range, Some(1), Some(repo.path())).expect("Error listing revisions");
+    assert_eq!(shas, vec![third]);
+}
+
+#[test]
+fn commit_changed_files() {
+    let repo = test_repo::TestRepo::new();
+    let root = repo.commit_file("old.txt", "old\n", "First commit");
+    repo.write("old.txt", "changed\n");
+    repo.write("new.txt", "new\n");
+    repo.git(&["add", "."]);
+    repo.git(&["commit", "-q", "-m", "Second commit"]);
+
+    let files = GitLib::commit_changed_files("HEAD", Some(repo.path()))
+        .expect("Error getting changed files");
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].status(), 'A');
+    assert_eq!(files[0].path(), &PathBuf::from("new.txt"));
+    assert_eq!(files[1].status(), 'M');
+    assert_eq!(files[1].path(), &PathBuf::from("old.txt"));
+
+    let files =
+        GitLib::commit_changed_files(&root, Some(repo.path())).expect("Error getting root files");
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].status(), 'A');
+    assert_eq!(files[0].path(), &PathBuf::from("old.txt"));
+}
+
+#[test]
+fn git_dir() {
+    let repo = test_repo::TestRepo::new();
+    let git_dir = GitLib::git_dir(Some(repo.path())).expect("Error getting git dir");
+    assert_eq!(git_dir, repo.path().join(".git"));
+}
+
+#[test]
+fn repo_state() {
+    let repo = test_repo::TestRepo::with_conflicting_branch();
+    assert_eq!(
+        GitLib::repo_state(Some(repo.path())).expect("Error getting state"),
+        RepoState::Clean
+    );
+    repo.git_fails(&["merge", "feature"]);
+    assert_eq!(
+        GitLib::repo_state(Some(repo.path())).expect("Error getting state"),
+        RepoState::Merge
+    );
+}
+
+#[test]
+fn abort_current() {
+    let repo = test_repo::TestRepo::with_conflicting_branch();
+    let head = repo.head();
+    repo.git_fails(&["merge", "feature"]);
+    GitLib::abort_current(Some(repo.path())).expect("Error aborting merge");
+    assert_eq!(
+        GitLib::repo_state(Some(repo.path())).expect("Error getting state"),
+        RepoState::Clean
+    );
+    assert_eq!(repo.head(), head);
+    assert_eq!(
+        std::fs::read_to_string(repo.path().join("file.txt")).expect("Error reading file"),
+        "main\n"
+    );
+}
+
+#[test]
+fn abort_current_am() {
+    let repo = test_repo::TestRepo::with_conflicting_branch();
+    let head = repo.head();
+    let dir = test_repo::TestRepo::empty_dir();
+    let patch = repo.git(&["format-patch", "-1", "feature", "-o", dir.path_str()]);
+    repo.git_fails(&["am", patch.trim_end_matches('\n')]);
+    assert_eq!(
+        GitLib::repo_state(Some(repo.path())).expect("Error getting state"),
+        RepoState::Am
+    );
+    GitLib::abort_current(Some(repo.path())).expect("Error aborting am");
+    assert_eq!(
+        GitLib::repo_state(Some(repo.path())).expect("Error getting state"),
+        RepoState::Clean
+    );
+    assert_eq!(repo.head(), head);
+}
+
+#[test]
+fn abort_current_nothing() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    match GitLib::abort_current(Some(repo.path())) {
+        Ok(_) => panic!("Nothing should have been aborted"),
+        Err(error) => assert_eq!(error.kind(), ErrorKind::NothingToAbort),
+    }
+}
+
+#[test]
+fn revert() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "one\n", "First commit");
+    let second = repo.commit_file("file.txt", "two\n", "Second commit");
+
+    match GitLib::revert(&second, false, Some(repo.path())).expect("Error reverting") {
+        RevertOutcome::Reverted(sha) => assert_eq!(sha, repo.head()),
+        outcome => panic!("Unexpected outcome {outcome:?}"),
+    }
+    assert_ne!(repo.head(), second);
+    assert_eq!(
+        std::fs::read_to_string(repo.path().join("file.txt")).expect("Error reading file"),
+        "one\n"
+    );
+}
+
+#[test]
+fn revert_no_commit() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "one\n", "First commit");
+    let second = repo.commit_file("file.txt", "two\n", "Second commit");
+
+    assert_eq!(
+        GitLib::revert(&second, true, Some(repo.path())).expect("Error reverting"),
+        RevertOutcome::Staged
+    );
+    assert_eq!(repo.head(), second);
+    assert_eq!(repo.git(&["diff", "--cached", "--name-only"]), "file.txt\n");
+}
+
+#[test]
+fn revert_conflict() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "one\n", "First commit");
+    let second = repo.commit_file("file.txt", "two\n", "Second commit");
+    repo.commit_file("file.txt", "three\n", "Third commit");
+
+    match GitLib::revert(&second, false, Some(repo.path())).expect("Error reverting") {
+        RevertOutcome::Conflict(files) => assert_eq!(files, vec![PathBuf::from("file.txt")]),
+        outcome => panic!("Unexpected outcome {outcome:?}"),
+    }
+    assert_eq!(
+        GitLib::repo_state(Some(repo.path())).expect("Error getting state"),
+        RepoState::Revert
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn bisect() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = test_repo::TestRepo::new();
+    let good = repo.commit_file("file.txt", "good\n", "First commit");
+    repo.commit_file("other.txt", "other\n", "Second commit");
+    let first_bad = repo.commit_file("file.txt", "bug\n", "Third commit");
+    repo.commit_file("other.txt", "more\n", "Fourth commit");
+    let bad = repo.commit_file("other.txt", "still more\n", "Fifth commit");
+
+    // The script lives outside the repository so checking out commits doesn't disturb it
+    let scripts = test_repo::TestRepo::empty_dir();
+    let script = scripts.path().join("test.sh");
+    std::fs::write(&script, "#!/bin/sh\n! grep -q bug file.txt\n").expect("Error writing script");
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))
+        .expect("Error making script executable");
+
+    GitLib::bisect_start(&bad, &good, Some(repo.path())).expect("Error starting bisect");
+    let found = GitLib::bisect_run(&script, Some(repo.path())).expect("Error running bisect");
+    assert_eq!(found, first_bad);
+    GitLib::bisect_reset(Some(repo.path())).expect("Error resetting bisect");
+    assert_eq!(repo.head(), bad);
+    assert_eq!(
+        GitLib::repo_state(Some(repo.path())).expect("Error getting state"),
+        RepoState::Clean
+    );
+}
+
+#[test]
+fn config_list() {
+    let repo = test_repo::TestRepo::new();
+    repo.git(&["config", "test.multiline", "first\nsecond"]);
+    let config = GitLib::config_list(Some(repo.path())).expect("Error listing config");
+    assert!(config.contains(&("user.name".to_string(), "Test User".to_string())));
+    assert!(config.contains(&("test.multiline".to_string(), "first\nsecond".to_string())));
+}
+
+#[test]
+fn config_list_origins() {
+    let repo = test_repo::TestRepo::new();
+    repo.git(&["config", "test.key", "value"]);
+    let config = GitLib::config_list_origins(Some(repo.path())).expect("Error listing config");
+    let entry = config
+        .iter()
+        .find(|entry| entry.key() == "test.key")
+        .expect("Key not listed");
+    assert_eq!(entry.value(), "value");
+    assert_eq!(entry.origin(), "file:.git/config");
+    assert_eq!(entry.origin_path(), Some(PathBuf::from(".git/config")));
+}
+
+#[test]
+fn stash_create_store() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    assert_eq!(
+        GitLib::stash_create(None, Some(repo.path())).expect("Error creating stash"),
+        None
+    );
+
+    repo.write("file.txt", "changed contents\n");
+    let oid = GitLib::stash_create(Some("Snapshot"), Some(repo.path()))
+        .expect("Error creating stash")
+        .expect("Nothing stashed");
+    // The working tree is left alone and nothing is added to the stash list yet
+    assert_eq!(
+        std::fs::read_to_string(repo.path().join("file.txt")).expect("Error reading file"),
+        "changed contents\n"
+    );
+    assert!(repo.git(&["stash", "list"]).is_empty());
+
+    GitLib::stash_store(&oid, Some("Snapshot"), Some(repo.path())).expect("Error storing stash");
+    assert_eq!(repo.git(&["rev-parse", "stash@{0}"]).trim_end(), oid);
+    assert!(repo.git(&["stash", "list"]).contains("Snapshot"));
+}
+
+#[test]
+fn commit_changed_files_rename_similarity() {
+    let repo = test_repo::TestRepo::new();
+    let contents: String = (1..=20).map(|line| format!("line {line}\n")).collect();
+    repo.commit_file("old.txt", &contents, "First commit");
+    repo.git(&["mv", "old.txt", "new.txt"]);
+    repo.write("new.txt", &contents.replace("line 20", "line twenty"));
+    repo.git(&["commit", "-q", "-am", "Rename and modify"]);
+
+    let files = GitLib::commit_changed_files("HEAD", Some(repo.path()))
+        .expect("Error getting changed files");
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].status(), 'R');
+    assert_eq!(files[0].path(), &PathBuf::from("new.txt"));
+    assert_eq!(files[0].original_path(), &Some(PathBuf::from("old.txt")));
+    let similarity = files[0].similarity().expect("No similarity");
+    assert!((50..100).contains(&similarity), "similarity {similarity}");
+}
+
+#[test]
+fn worktree_lock() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    let dir = test_repo::TestRepo::empty_dir();
+    let worktree = dir.path().join("linked");
+    repo.git(&[
+        "worktree",
+        "add",
+        "-q",
+        worktree.to_str().unwrap(),
+        "-b",
+        "linked",
+    ]);
+
+    GitLib::worktree_lock(&worktree, Some("On removable media"), Some(repo.path()))
+        .expect("Error locking worktree");
+    let worktrees = GitLib::worktree_list(Some(repo.path())).expect("Error listing worktrees");
+    assert_eq!(worktrees.len(), 2);
+    assert_eq!(worktrees[0].path(), repo.path());
+    assert!(!worktrees[0].locked());
+    assert_eq!(worktrees[1].path(), &worktree);
+    assert_eq!(worktrees[1].branch().as_deref(), Some("refs/heads/linked"));
+    assert!(worktrees[1].locked());
+    assert_eq!(
+        worktrees[1].lock_reason().as_deref(),
+        Some("On removable media")
+    );
+
+    GitLib::worktree_unlock(&worktree, Some(repo.path())).expect("Error unlocking worktree");
+    let worktrees = GitLib::worktree_list(Some(repo.path())).expect("Error listing worktrees");
+    assert!(!worktrees[1].locked());
+}
+
+#[test]
+fn range_diff() {
+    let repo = test_repo::TestRepo::new();
+    let base = repo.commit_file("file.txt", "base\n", "Base commit");
+    repo.git(&["checkout", "-q", "-b", "feature"]);
+    repo.commit_file("feature.txt", "feature\n", "Feature commit");
+    let old_tip = repo.head();
+    repo.git(&["checkout", "-q", "main"]);
+    repo.commit_file("main.txt", "main\n", "Main commit");
+    repo.git(&["checkout", "-q", "feature"]);
+    repo.git(&["rebase", "-q", "main"]);
+    let new_tip = repo.head();
+
+    let range_diff = GitLib::range_diff(&base, &old_tip, &new_tip, Some(repo.path()))
+        .expect("Error getting range diff");
+    assert!(!range_diff.is_empty());
+    assert!(range_diff.contains("Feature commit"));
+}
+
+#[test]
+fn path_history() {
+    let repo = test_repo::TestRepo::new();
+    let contents: String = (1..=20).map(|line| format!("line {line}\n")).collect();
+    let first = repo.commit_file("old.txt", &contents, "Add old");
+    repo.commit_file("other.txt", "other\n", "Unrelated");
+    repo.git(&["mv", "old.txt", "new.txt"]);
+    repo.git(&["commit", "-q", "-m", "Rename"]);
+    let renamed = repo.head();
+    let changed = repo.commit_file("new.txt", &format!("{contents}line 21\n"), "Change new");
+
+    let history =
+        GitLib::path_history("new.txt", None, Some(repo.path())).expect("Error getting history");
+    let shas: Vec<&str> = history.iter().map(|commit| commit.sha()).collect();
+    assert_eq!(
+        shas,
+        vec![changed.as_str(), renamed.as_str(), first.as_str()]
+    );
+    assert_eq!(history[2].subject(), "Add old");
+    assert_eq!(history[2].author_name(), "Test User");
+    assert_eq!(history[2].author_email(), "test@example.com");
+
+    let history =
+        GitLib::path_history("new.txt", Some(1), Some(repo.path())).expect("Error getting history");
+    assert_eq!(history.len(), 1);
+}
+
+#[test]
+fn diff_has_changes() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    assert!(!GitLib::diff_has_changes(false, Some(repo.path())).expect("Error diffing"));
+    assert!(!GitLib::diff_has_changes(true, Some(repo.path())).expect("Error diffing"));
+
+    repo.write("file.txt", "changed\n");
+    assert!(GitLib::diff_has_changes(false, Some(repo.path())).expect("Error diffing"));
+    assert!(!GitLib::diff_has_changes(true, Some(repo.path())).expect("Error diffing"));
+
+    repo.git(&["add", "file.txt"]);
+    assert!(!GitLib::diff_has_changes(false, Some(repo.path())).expect("Error diffing"));
+    assert!(GitLib::diff_has_changes(true, Some(repo.path())).expect("Error diffing"));
+}
+
+#[test]
+fn diff_has_changes_not_repo() {
+    let dir = test_repo::TestRepo::empty_dir();
+    assert!(GitLib::diff_has_changes(false, Some(dir.path())).is_err());
+}
+
+#[test]
+fn config_get_bool() {
+    let repo = test_repo::TestRepo::new();
+    assert_eq!(
+        GitLib::config_get_bool("core.bare", Some(repo.path())).expect("Error getting config"),
+        Some(false)
+    );
+    repo.git(&["config", "test.flag", "yes"]);
+    assert_eq!(
+        GitLib::config_get_bool("test.flag", Some(repo.path())).expect("Error getting config"),
+        Some(true)
+    );
+    assert_eq!(
+        GitLib::config_get_bool("test.missing", Some(repo.path())).expect("Error getting config"),
+        None
+    );
+    repo.git(&["config", "test.invalid", "maybe"]);
+    assert!(GitLib::config_get_bool("test.invalid", Some(repo.path())).is_err());
+}
+
+#[test]
+fn config_get_int() {
+    let repo = test_repo::TestRepo::new();
+    repo.git(&["config", "test.size", "2k"]);
+    assert_eq!(
+        GitLib::config_get_int("test.size", Some(repo.path())).expect("Error getting config"),
+        Some(2048)
+    );
+    assert_eq!(
+        GitLib::config_get_int("test.missing", Some(repo.path())).expect("Error getting config"),
+        None
+    );
+}
+
+#[test]
+fn stash_branch() {
+    let repo = test_repo::TestRepo::new();
+    let base = repo.commit_file("file.txt", "base\n", "First commit");
+    repo.write("file.txt", "stashed\n");
+    repo.git(&["stash", "-q"]);
+    // Advance the branch so the stash no longer applies cleanly
+    repo.commit_file("file.txt", "advanced\n", "Second commit");
+
+    GitLib::stash_branch("recovered", Some(0), Some(repo.path())).expect("Error branching stash");
+    assert_eq!(
+        repo.git(&["branch", "--show-current"]).trim_end(),
+        "recovered"
+    );
+    assert_eq!(repo.head(), base);
+    assert_eq!(
+        std::fs::read_to_string(repo.path().join("file.txt")).expect("Error reading file"),
+        "stashed\n"
+    );
+    assert!(repo.git(&["stash", "list"]).is_empty());
+}
+
+#[test]
+fn remote_head_oid() {
+    let remote = test_repo::TestRepo::bare();
+    let repo = test_repo::TestRepo::new();
+    let sha = repo.commit_file("file.txt", "contents\n", "First commit");
+    GitLib::remote_add("origin", remote.path_str(), Some(repo.path()))
+        .expect("Error adding remote");
+    repo.git(&["push", "-q", "origin", "main"]);
+
+    assert_eq!(
+        GitLib::remote_head_oid("origin", "refs/heads/main", Some(repo.path()))
+            .expect("Error querying remote"),
+        Some(sha)
+    );
+    assert_eq!(
+        GitLib::remote_head_oid("origin", "refs/heads/missing", Some(repo.path()))
+            .expect("Error querying remote"),
+        None
+    );
+}
+
+#[test]
+fn commit_message() {
+    let repo = test_repo::TestRepo::new();
+    let message = "Subject line\n\nFirst paragraph of the body.\n\nSecond paragraph.\n";
+    repo.write("file.txt", "contents\n");
+    repo.git(&["add", "file.txt"]);
+    repo.git(&["commit", "-q", "-m", message]);
+
+    assert_eq!(
+        GitLib::commit_message("HEAD", Some(repo.path())).expect("Error getting message"),
+        message
+    );
+}
+
+#[test]
+fn commit_trailers() {
+    let repo = test_repo::TestRepo::new();
+    let message = "Subject line\n\nBody.\n\n\
+                   Co-authored-by: Alice <alice@example.com>\n\
+                   Co-authored-by: Bob <bob@example.com>\n";
+    repo.write("file.txt", "contents\n");
+    repo.git(&["add", "file.txt"]);
+    repo.git(&["commit", "-q", "-m", message]);
+
+    assert_eq!(
+        GitLib::commit_trailers("HEAD", Some(repo.path())).expect("Error getting trailers"),
+        vec![
+            (
+                "Co-authored-by".to_string(),
+                "Alice <alice@example.com>".to_string()
+            ),
+            (
+                "Co-authored-by".to_string(),
+                "Bob <bob@example.com>".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn add_trailer() {
+    let message = "Subject line\n\nBody.\n\nSigned-off-by: Alice <alice@example.com>\n";
+    let message = GitLib::add_trailer(message, "Reviewed-by", "Bob <bob@example.com>")
+        .expect("Error adding trailer");
+    assert_eq!(
+        message,
+        "Subject line\n\nBody.\n\n\
+         Signed-off-by: Alice <alice@example.com>\n\
+         Reviewed-by: Bob <bob@example.com>\n"
+    );
+}
+
+#[test]
+fn current_upstream() {
+    let remote = test_repo::TestRepo::bare();
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    GitLib::remote_add("origin", remote.path_str(), Some(repo.path()))
+        .expect("Error adding remote");
+    assert_eq!(
+        GitLib::current_upstream(Some(repo.path())).expect("Error getting upstream"),
+        None
+    );
+
+    repo.git(&["push", "-q", "--set-upstream", "origin", "main"]);
+    assert_eq!(
+        GitLib::current_upstream(Some(repo.path())).expect("Error getting upstream"),
+        Some("origin/main".to_string())
+    );
+}
+
+#[test]
+fn current_upstream_not_repo() {
+    let dir = test_repo::TestRepo::empty_dir();
+    assert!(GitLib::current_upstream(Some(dir.path())).is_err());
+}
+
+#[test]
+fn object_size() {
+    let repo = test_repo::TestRepo::new();
+    repo.write("file.txt", "twelve bytes");
+    let oid = repo.git(&["hash-object", "-w", "file.txt"]);
+    assert_eq!(
+        GitLib::object_size(oid.trim_end(), Some(repo.path())).expect("Error getting size"),
+        12
+    );
+    assert!(GitLib::object_size(&"0".repeat(40), Some(repo.path())).is_err());
+}
+
+#[test]
+fn ls_tree() {
+    let repo = test_repo::TestRepo::new();
+    repo.write("file.txt", "contents\n");
+    repo.write("dir/nested file.txt", "nested\n");
+    repo.git(&["add", "."]);
+    repo.git(&["commit", "-q", "-m", "First commit"]);
+
+    let entries = GitLib::ls_tree("HEAD", false, Some(repo.path())).expect("Error listing tree");
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].path(), &PathBuf::from("dir"));
+    assert_eq!(entries[0].kind(), ObjectType::Tree);
+    assert_eq!(entries[0].mode(), "040000");
+    assert_eq!(entries[1].path(), &PathBuf::from("file.txt"));
+    assert_eq!(entries[1].kind(), ObjectType::Blob);
+    assert_eq!(entries[1].mode(), "100644");
+    assert_eq!(
+        entries[1].oid(),
+        repo.git(&["rev-parse", "HEAD:file.txt"]).trim_end()
+    );
+}
+
+#[test]
+fn ls_tree_recursive() {
+    let repo = test_repo::TestRepo::new();
+    repo.write("file.txt", "contents\n");
+    repo.write("dir/nested file.txt", "nested\n");
+    repo.git(&["add", "."]);
+    repo.git(&["commit", "-q", "-m", "First commit"]);
+
+    let entries = GitLib::ls_tree("HEAD", true, Some(repo.path())).expect("Error listing tree");
+    let paths: Vec<&PathBuf> = entries.iter().map(|entry| entry.path()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            &PathBuf::from("dir/nested file.txt"),
+            &PathBuf::from("file.txt")
+        ]
+    );
+    assert!(entries.iter().all(|entry| entry.kind() == ObjectType::Blob));
+}
+
+#[test]
+fn mktree() {
+    let repo = test_repo::TestRepo::new();
+    repo.write("file.txt", "contents\n");
+    repo.git(&["add", "file.txt"]);
+    let written = repo.git(&["write-tree"]);
+    let blob = repo.git(&["hash-object", "-w", "file.txt"]);
+
+    let entries = [TreeEntry::new(
+        "100644",
+        ObjectType::Blob,
+        blob.trim_end(),
+        "file.txt",
+    )];
+    let tree = GitLib::mktree(&entries, Some(repo.path())).expect("Error making tree");
+    assert_eq!(tree, written.trim_end());
+}
+
+#[test]
+fn mktree_invalid_mode() {
+    let repo = test_repo::TestRepo::new();
+    let entries = [TreeEntry::new(
+        "100664",
+        ObjectType::Blob,
+        &"0".repeat(40),
+        "file.txt",
+    )];
+    assert!(GitLib::mktree(&entries, Some(repo.path())).is_err());
+}
+
+#[test]
+fn replace() {
+    let repo = test_repo::TestRepo::new();
+    let first = repo.commit_file("file.txt", "one\n", "First commit");
+    let second = repo.commit_file("file.txt", "two\n", "Second commit");
+    assert!(GitLib::replace_list(Some(repo.path()))
+        .expect("Error listing replacements")
+        .is_empty());
+
+    GitLib::replace_add(&first, &second, Some(repo.path())).expect("Error adding replacement");
+    assert_eq!(
+        GitLib::replace_list(Some(repo.path())).expect("Error listing replacements"),
+        vec![first.clone()]
+    );
+    assert_eq!(
+        GitLib::commit_message(&first, Some(repo.path())).expect("Error getting message"),
+        "Second commit\n"
+    );
+
+    GitLib::replace_remove(&first, Some(repo.path())).expect("Error removing replacement");
+    assert!(GitLib::replace_list(Some(repo.path()))
+        .expect("Error listing replacements")
+        .is_empty());
+}
+
+#[test]
+fn prune() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.write("unreachable.txt", "unreachable\n");
+    let oid = repo.git(&["hash-object", "-w", "unreachable.txt"]);
+    let oid = oid.trim_end().to_owned();
+
+    assert_eq!(
+        GitLib::prune(true, Some(repo.path())).expect("Error pruning"),
+        vec![oid.clone()]
+    );
+    assert!(GitLib::object_size(&oid, Some(repo.path())).is_ok());
+
+    assert_eq!(
+        GitLib::prune(false, Some(repo.path())).expect("Error pruning"),
+        vec![oid.clone()]
+    );
+    assert!(GitLib::object_size(&oid, Some(repo.path())).is_err());
+}
+
+#[test]
+fn verify_pack() {
+    let repo = test_repo::TestRepo::new();
+    for count in 1..=3 {
+        let contents: String = (1..=count * 50).map(|line| format!("{line}\n")).collect();
+        repo.commit_file("file.txt", &contents, "Commit");
+    }
+    repo.git(&["gc", "-q"]);
+    let pack_dir = repo.path().join(".git/objects/pack");
+    let idx = std::fs::read_dir(pack_dir)
+        .expect("Error reading pack directory")
+        .map(|entry| entry.expect("Error reading entry").path())
+        .find(|path| path.extension().is_some_and(|extension| extension == "idx"))
+        .expect("No pack index");
+
+    let stats = GitLib::verify_pack(&idx, Some(repo.path())).expect("Error verifying pack");
+    // Three commits, three trees and three blobs
+    assert_eq!(stats.total_objects(), 9);
+    let deltas: u64 = stats.chain_lengths().values().sum();
+    assert_eq!(stats.non_delta_objects() + deltas, stats.total_objects());
+    assert!(stats.size() > 0);
+    assert!(stats.size_in_pack() > 0);
+}
+
+#[test]
+fn repack() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    let count_packs = || {
+        std::fs::read_dir(repo.path().join(".git/objects/pack"))
+            .expect("Error reading pack directory")
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .expect("Error reading entry")
+                    .path()
+                    .extension()
+                    .is_some_and(|extension| extension == "pack")
+            })
+            .count()
+    };
+    assert_eq!(count_packs(), 0);
+
+    GitLib::repack(true, true, Some(repo.path())).expect("Error repacking");
+    assert_eq!(count_packs(), 1);
+    assert!(repo.git(&["count-objects", "-v"]).contains("in-pack: 3"));
+}
+
+#[test]
+fn get_commit() {
+    let repo = test_repo::TestRepo::new();
+    let parent = repo.commit_file("file.txt", "one\n", "First commit");
+    repo.write("file.txt", "two\n");
+    repo.git(&["add", "file.txt"]);
+    repo.git(&[
+        "commit",
+        "-q",
+        "--author=Alice Author <alice@example.com>",
+        "--date=2020-01-02T03:04:05Z",
+        "-m",
+        "Subject line\n\nBody line one.\nBody line two.",
+    ]);
+    let sha = repo.head();
+
+    let commit = GitLib::get_commit("HEAD", Some(repo.path())).expect("Error getting commit");
+    assert_eq!(commit.sha(), sha);
+    assert_eq!(
+        commit.tree(),
+        repo.git(&["rev-parse", "HEAD^{tree}"]).trim_end()
+    );
+    assert_eq!(commit.parents(), &vec![parent]);
+    assert_eq!(commit.author_name(), "Alice Author");
+    assert_eq!(commit.author_email(), "alice@example.com");
+    assert_eq!(commit.author_date(), "2020-01-02T03:04:05+00:00");
+    assert_eq!(commit.committer_name(), "Test User");
+    assert_eq!(commit.committer_email(), "test@example.com");
+    assert_ne!(commit.committer_date(), commit.author_date());
+    assert_eq!(commit.subject(), "Subject line");
+    assert_eq!(commit.body(), "Body line one.\nBody line two.");
+}
+
+#[test]
+fn commit() {
+    let repo = test_repo::TestRepo::new();
+    repo.write("file.txt", "contents\n");
+    repo.git(&["add", "file.txt"]);
+    let author =
+        Identity::new("Alice Author", "alice@example.com").with_date("2001-02-03T04:05:06Z");
+    let committer =
+        Identity::new("Carol Committer", "carol@example.com").with_date("2002-03-04T05:06:07Z");
+    let sha = GitLib::commit(
+        "Import",
+        Some(&author),
+        Some(&committer),
+        false,
+        Some(repo.path()),
+    )
+    .expect("Error committing");
+    assert_eq!(sha, repo.head());
+
+    let commit = GitLib::get_commit(&sha, Some(repo.path())).expect("Error getting commit");
+    assert_eq!(commit.subject(), "Import");
+    assert_eq!(commit.author_name(), "Alice Author");
+    assert_eq!(commit.author_email(), "alice@example.com");
+    assert_eq!(commit.author_date(), "2001-02-03T04:05:06+00:00");
+    assert_eq!(commit.committer_name(), "Carol Committer");
+    assert_eq!(commit.committer_email(), "carol@example.com");
+    assert_eq!(commit.committer_date(), "2002-03-04T05:06:07+00:00");
+    // The configuration wasn't touched
+    assert_eq!(repo.git(&["config", "user.name"]).trim_end(), "Test User");
+}
+
+#[test]
+fn diff_stash() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "original\n", "First commit");
+    repo.write("file.txt", "stashed\n");
+    repo.git(&["stash", "-q"]);
+    repo.write("file.txt", "current\n");
+
+    let diff = GitLib::diff_stash(0, Some(repo.path())).expect("Error diffing stash");
+    assert!(diff.contains("-stashed"));
+    assert!(diff.contains("+current"));
+    assert!(GitLib::diff_stash(1, Some(repo.path())).is_err());
+}
+
+#[test]
+fn rerere() {
+    let repo = test_repo::TestRepo::with_conflicting_branch();
+    repo.git(&["config", "rerere.enabled", "true"]);
+    repo.git_fails(&["merge", "feature"]);
+
+    assert_eq!(
+        GitLib::rerere_status(Some(repo.path())).expect("Error getting rerere status"),
+        vec![PathBuf::from("file.txt")]
+    );
+    GitLib::rerere_clear(Some(repo.path())).expect("Error clearing rerere");
+    assert!(GitLib::rerere_status(Some(repo.path()))
+        .expect("Error getting rerere status")
+        .is_empty());
+}
+
+#[test]
+fn tag_message() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.git(&["tag", "-a", "v1.0", "-m", "Release 1.0\n\nRelease notes."]);
+    repo.git(&["tag", "lightweight"]);
+
+    assert_eq!(
+        GitLib::tag_message("v1.0", Some(repo.path())).expect("Error getting tag message"),
+        Some("Release 1.0\n\nRelease notes.\n".to_string())
+    );
+    assert_eq!(
+        GitLib::tag_message("lightweight", Some(repo.path())).expect("Error getting tag message"),
+        None
+    );
+    assert!(GitLib::tag_message("missing", Some(repo.path())).is_err());
+}
+
+#[test]
+fn tag_info_annotated() {
+    let repo = test_repo::TestRepo::new();
+    let sha = repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.git(&["tag", "-a", "v1.0", "-m", "Release 1.0"]);
+
+    let tag = GitLib::tag_info("v1.0", Some(repo.path())).expect("Error getting tag");
+    assert_eq!(tag.name(), "v1.0");
+    assert!(tag.is_annotated());
+    assert_eq!(tag.target_oid(), sha);
+    assert_eq!(tag.tagger_name().as_deref(), Some("Test User"));
+    assert_eq!(tag.tagger_email().as_deref(), Some("test@example.com"));
+    assert!(tag.tagger_date().is_some());
+    assert_eq!(tag.message().as_deref(), Some("Release 1.0\n"));
+}
+
+#[test]
+fn tag_info_lightweight() {
+    let repo = test_repo::TestRepo::new();
+    let sha = repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.git(&["tag", "lightweight"]);
+
+    let tag = GitLib::tag_info("lightweight", Some(repo.path())).expect("Error getting tag");
+    assert_eq!(tag.name(), "lightweight");
+    assert!(!tag.is_annotated());
+    assert_eq!(tag.target_oid(), sha);
+    assert_eq!(tag.tagger_name(), &None);
+    assert_eq!(tag.tagger_email(), &None);
+    assert_eq!(tag.tagger_date(), &None);
+    assert_eq!(tag.message(), &None);
+    assert!(GitLib::tag_info("missing", Some(repo.path())).is_err());
+}
+
+#[test]
+fn branches_containing() {
+    let remote = test_repo::TestRepo::bare();
+    let repo = test_repo::TestRepo::new();
+    let base = repo.commit_file("file.txt", "base\n", "Base commit");
+    GitLib::remote_add("origin", remote.path_str(), Some(repo.path()))
+        .expect("Error adding remote");
+    repo.git(&["branch", "other"]);
+    repo.git(&["checkout", "-q", "-b", "feature"]);
+    let fix = repo.commit_file("file.txt", "fix\n", "Fix");
+    repo.git(&["push", "-q", "origin", "feature"]);
+    repo.git(&["checkout", "-q", "--detach", "main"]);
+
+    assert_eq!(
+        GitLib::branches_containing(&fix, false, Some(repo.path())).expect("Error listing"),
+        vec!["feature".to_string()]
+    );
+    assert_eq!(
+        GitLib::branches_containing(&fix, true, Some(repo.path())).expect("Error listing"),
+        vec!["feature".to_string(), "origin/feature".to_string()]
+    );
+    assert_eq!(
+        GitLib::branches_containing(&base, false, Some(repo.path())).expect("Error listing"),
+        vec![
+            "feature".to_string(),
+            "main".to_string(),
+            "other".to_string()
+        ]
+    );
+}
+
+#[test]
+fn tags_containing() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "one\n", "First commit");
+    repo.git(&["tag", "v1.0"]);
+    let fix = repo.commit_file("file.txt", "fix\n", "Fix");
+    repo.git(&["tag", "v1.1"]);
+    repo.commit_file("file.txt", "more\n", "More");
+    repo.git(&["tag", "v2.0"]);
+
+    assert_eq!(
+        GitLib::tags_containing(&fix, Some(repo.path())).expect("Error listing tags"),
+        vec!["v1.1".to_string(), "v2.0".to_string()]
+    );
+}
+
+#[test]
+fn stash_count() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    assert_eq!(
+        GitLib::stash_count(Some(repo.path())).expect("Error counting"),
+        0
+    );
+    repo.write("file.txt", "first\n");
+    repo.git(&["stash", "-q"]);
+    assert_eq!(
+        GitLib::stash_count(Some(repo.path())).expect("Error counting"),
+        1
+    );
+    repo.write("file.txt", "second\n");
+    repo.git(&["stash", "-q"]);
+    assert_eq!(
+        GitLib::stash_count(Some(repo.path())).expect("Error counting"),
+        2
+    );
+}
+
+#[test]
+fn worktree_move() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    let dir = test_repo::TestRepo::empty_dir();
+    let from = dir.path().join("from");
+    let to = dir.path().join("to");
+    repo.git(&[
+        "worktree",
+        "add",
+        "-q",
+        from.to_str().unwrap(),
+        "-b",
+        "linked",
+    ]);
+
+    GitLib::worktree_lock(&from, None, Some(repo.path())).expect("Error locking worktree");
+    assert!(GitLib::worktree_move(&from, &to, Some(repo.path())).is_err());
+    GitLib::worktree_unlock(&from, Some(repo.path())).expect("Error unlocking worktree");
+
+    GitLib::worktree_move(&from, &to, Some(repo.path())).expect("Error moving worktree");
+    let worktrees = GitLib::worktree_list(Some(repo.path())).expect("Error listing worktrees");
+    assert_eq!(worktrees.len(), 2);
+    assert_eq!(worktrees[1].path(), &to);
+    assert!(to.join("file.txt").exists());
+    assert!(!from.exists());
+}
+
+#[test]
+fn log_graph() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "base\n", "Base commit");
+    repo.git(&["checkout", "-q", "-b", "feature"]);
+    repo.commit_file("feature.txt", "feature\n", "Feature commit");
+    repo.git(&["checkout", "-q", "main"]);
+    repo.commit_file("main.txt", "main\n", "Main commit");
+    repo.git(&["merge", "-q", "--no-ff", "-m", "Merge feature", "feature"]);
+
+    let graph = GitLib::log_graph(None, Some(repo.path())).expect("Error getting graph");
+    assert!(graph.contains("|\\"));
+    assert!(graph.contains("|/"));
+    assert!(graph.contains("(HEAD -> main)"));
+    assert!(graph.contains("Feature commit"));
+
+    let graph = GitLib::log_graph(Some(1), Some(repo.path())).expect("Error getting graph");
+    assert_eq!(graph.matches('*').count(), 1);
+}
+
+#[test]
+fn shortstat() {
+    let repo = test_repo::TestRepo::new();
+    let base = repo.commit_file("file.txt", "one\ntwo\n", "Base commit");
+    repo.commit_file("file.txt", "one\n2\nthree\n", "Change file");
+    repo.commit_file("other.txt", "other\n", "Add other");
+
+    let range = format!("{base}..HEAD");
+    assert_eq!(
+        GitLib::shortstat(&range, Some(repo.path())).expect("Error getting stats"),
+        (2, 3, 1)
+    );
+    assert_eq!(
+        GitLib::shortstat("HEAD..HEAD", Some(repo.path())).expect("Error getting stats"),
+        (0, 0, 0)
+    );
+}
+
+#[test]
+fn root_commit() {
+    let repo = test_repo::TestRepo::new();
+    let first = repo.commit_file("file.txt", "one\n", "First commit");
+    repo.commit_file("file.txt", "two\n", "Second commit");
+    repo.commit_file("file.txt", "three\n", "Third commit");
+    assert_eq!(
+        GitLib::root_commit(Some(repo.path())).expect("Error getting root"),
+        first
+    );
+}
+
+#[test]
+fn root_commit_no_commits() {
+    let repo = test_repo::TestRepo::new();
+    match GitLib::root_commit(Some(repo.path())) {
+        Ok(sha) => panic!("Unexpected root {sha}"),
+        Err(error) => assert_eq!(error.kind(), ErrorKind::NoCommits),
+    }
+}
+
+#[test]
+fn stash_push_include_untracked() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.write("file.txt", "changed\n");
+    repo.write("untracked.txt", "untracked\n");
+
+    GitLib::stash_push(Some("Work in progress"), true, false, Some(repo.path()))
+        .expect("Error stashing");
+    assert!(!repo.path().join("untracked.txt").exists());
+    assert_eq!(
+        std::fs::read_to_string(repo.path().join("file.txt")).expect("Error reading file"),
+        "contents\n"
+    );
+    assert!(repo.git(&["stash", "list"]).contains("Work in progress"));
+
+    repo.git(&["stash", "pop", "-q"]);
+    assert_eq!(
+        std::fs::read_to_string(repo.path().join("untracked.txt")).expect("Error reading file"),
+        "untracked\n"
+    );
+}
+
+#[test]
+fn stash_push_keep_index() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.write("file.txt", "staged\n");
+    repo.git(&["add", "file.txt"]);
+    repo.write("untracked.txt", "untracked\n");
+
+    GitLib::stash_push(None, false, true, Some(repo.path())).expect("Error stashing");
+    // The staged change is still there, and the untracked file was left alone
+    assert!(GitLib::diff_has_changes(true, Some(repo.path())).expect("Error diffing"));
+    assert!(repo.path().join("untracked.txt").exists());
+    assert_eq!(
+        GitLib::stash_count(Some(repo.path())).expect("Error counting"),
+        1
+    );
+}
+
+#[test]
+fn blame_raw() {
+    let repo = test_repo::TestRepo::new();
+    let sha = repo.commit_file("file.txt", "one\ntwo\n", "First commit");
+    let blame = GitLib::blame_raw("file.txt", Some(repo.path())).expect("Error blaming");
+    assert!(blame.lines().any(|line| line == "filename file.txt"));
+    assert!(blame.starts_with(&sha));
+    assert!(blame.contains("\tone\n"));
+}
+
+#[test]
+fn diff_words() {
+    let repo = test_repo::TestRepo::new();
+    let from = repo.commit_file("doc.md", "The quick brown fox\n", "First commit");
+    let to = repo.commit_file("doc.md", "The quick red fox\n", "Second commit");
+
+    let diff =
+        GitLib::diff_words(&from, &to, Some("doc.md"), Some(repo.path())).expect("Error diffing");
+    let lines: Vec<&str> = diff.lines().collect();
+    assert!(lines.contains(&" The quick "));
+    assert!(lines.contains(&"-brown"));
+    assert!(lines.contains(&"+red"));
+    assert!(lines.contains(&"  fox"));
+    assert!(lines.contains(&"~"));
+}
+
+#[test]
+fn blame_at() {
+    let repo = test_repo::TestRepo::new();
+    let first = repo.commit_file("file.txt", "one\ntwo\n", "First commit");
+    repo.git(&["tag", "v1.0"]);
+    repo.git(&["config", "user.name", "Other User"]);
+    let second = repo.commit_file("file.txt", "one\n2\nthree\n", "Second commit");
+
+    let lines = GitLib::blame_at("file.txt", "v1.0", Some(repo.path())).expect("Error blaming");
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|line| line.sha() == first));
+    assert!(lines.iter().all(|line| line.author() == "Test User"));
+    assert_eq!(lines[1].line_number(), 2);
+    assert_eq!(lines[1].content(), "two");
+
+    let lines = GitLib::blame("file.txt", Some(repo.path())).expect("Error blaming");
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0].sha(), first);
+    assert_eq!(lines[1].sha(), second);
+    assert_eq!(lines[1].author(), "Other User");
+    assert_eq!(lines[2].content(), "three");
+}
+
+#[test]
+fn conflict_versions() {
+    let repo = test_repo::TestRepo::with_conflicting_branch();
+    repo.git_fails(&["merge", "feature"]);
+
+    let versions =
+        GitLib::conflict_versions("file.txt", Some(repo.path())).expect("Error getting versions");
+    assert_eq!(versions.base(), &Some(b"base\n".to_vec()));
+    assert_eq!(versions.ours(), &Some(b"main\n".to_vec()));
+    assert_eq!(versions.theirs(), &Some(b"feature\n".to_vec()));
+}
+
+#[test]
+fn conflict_versions_subdirectory() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("dir/file.txt", "base\n", "Base commit");
+    repo.git(&["checkout", "-q", "-b", "feature"]);
+    repo.commit_file("dir/file.txt", "feature\n", "Feature commit");
+    repo.git(&["checkout", "-q", "main"]);
+    repo.commit_file("dir/file.txt", "main\n", "Main commit");
+    repo.git_fails(&["merge", "feature"]);
+
+    let dir = repo.path().join("dir");
+    let versions =
+        GitLib::conflict_versions("file.txt", Some(&dir)).expect("Error getting versions");
+    assert_eq!(versions.base(), &Some(b"base\n".to_vec()));
+    assert_eq!(versions.ours(), &Some(b"main\n".to_vec()));
+    assert_eq!(versions.theirs(), &Some(b"feature\n".to_vec()));
+}
+
+#[test]
+fn conflict_versions_no_base() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("README", "readme\n", "First commit");
+    repo.git(&["checkout", "-q", "-b", "feature"]);
+    repo.commit_file("new.txt", "feature\n", "Feature commit");
+    repo.git(&["checkout", "-q", "main"]);
+    repo.commit_file("new.txt", "main\n", "Main commit");
+    repo.git_fails(&["merge", "feature"]);
+
+    let versions =
+        GitLib::conflict_versions("new.txt", Some(repo.path())).expect("Error getting versions");
+    assert_eq!(versions.base(), &None);
+    assert_eq!(versions.ours(), &Some(b"main\n".to_vec()));
+    assert_eq!(versions.theirs(), &Some(b"feature\n".to_vec()));
+    assert!(GitLib::conflict_versions("README", Some(repo.path())).is_err());
+}
+
+#[test]
+fn resolve_conflict() {
+    let repo = test_repo::TestRepo::with_conflicting_branch();
+    repo.git_fails(&["merge", "feature"]);
+
+    GitLib::resolve_conflict("file.txt", b"resolved\n", Some(repo.path()))
+        .expect("Error resolving conflict");
+    assert_eq!(
+        std::fs::read_to_string(repo.path().join("file.txt")).expect("Error reading file"),
+        "resolved\n"
+    );
+    assert_eq!(repo.git(&["ls-files", "--unmerged"]), "");
+    assert_eq!(repo.git(&["diff", "--cached", "--name-only"]), "file.txt\n");
+
+    assert!(GitLib::resolve_conflict("file.txt", b"again\n", Some(repo.path())).is_err());
+}
+
+#[test]
+fn merge_message() {
+    let repo = test_repo::TestRepo::with_conflicting_branch();
+    assert_eq!(
+        GitLib::merge_message(Some(repo.path())).expect("Error getting message"),
+        None
+    );
+
+    repo.git_fails(&["merge", "feature"]);
+    let message = GitLib::merge_message(Some(repo.path()))
+        .expect("Error getting message")
+        .expect("No merge message");
+    assert!(message.starts_with("Merge branch 'feature'"));
+    assert!(message.contains("file.txt"));
+}
+
+#[test]
+fn merge_message_cherry_pick() {
+    let repo = test_repo::TestRepo::with_conflicting_branch();
+    repo.git_fails(&["cherry-pick", "feature"]);
+    assert!(repo.path().join(".git/MERGE_MSG").is_file());
+    assert_eq!(
+        GitLib::merge_message(Some(repo.path())).expect("Error getting message"),
+        None
+    );
+}
+
+#[test]
+fn remote_prune() {
+    let origin = test_repo::TestRepo::new();
+    origin.commit_file("file.txt", "contents\n", "First commit");
+    origin.git(&["branch", "feature"]);
+    let repo = test_repo::TestRepo::clone_of(&origin);
+    origin.git(&["branch", "-D", "feature"]);
+
+    let pruned = GitLib::remote_prune("origin", true, Some(repo.path())).expect("Error pruning");
+    assert_eq!(pruned, vec!["origin/feature"]);
+    assert!(repo.git(&["branch", "-r"]).contains("origin/feature"));
+
+    let pruned = GitLib::remote_prune("origin", false, Some(repo.path())).expect("Error pruning");
+    assert_eq!(pruned, vec!["origin/feature"]);
+    assert!(!repo.git(&["branch", "-r"]).contains("origin/feature"));
+}
+
+#[test]
+fn is_shallow() {
+    let origin = test_repo::TestRepo::new();
+    origin.commit_file("file.txt", "one\n", "First commit");
+    origin.commit_file("file.txt", "two\n", "Second commit");
+    let full = test_repo::TestRepo::clone_of(&origin);
+    assert!(!GitLib::is_shallow(Some(full.path())).expect("Error checking clone"));
+
+    // --depth is ignored for local clones unless the origin is given as a URL
+    let shallow = test_repo::TestRepo::empty_dir();
+    let url = format!("file://{}", origin.path_str());
+    shallow.git(&["clone", "-q", "--depth", "1", url.as_str(), "."]);
+    assert!(GitLib::is_shallow(Some(shallow.path())).expect("Error checking clone"));
+}
+
+#[test]
+fn stash_pop_restore_index() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.write("file.txt", "staged\n");
+    repo.git(&["add", "file.txt"]);
+    GitLib::stash_push(None, false, false, Some(repo.path())).expect("Error stashing");
+
+    assert_eq!(
+        GitLib::stash_pop(None, true, Some(repo.path())).expect("Error popping"),
+        StashOutcome::Applied
+    );
+    assert_eq!(repo.git(&["diff", "--cached", "--name-only"]), "file.txt\n");
+    assert_eq!(
+        GitLib::stash_count(Some(repo.path())).expect("Error counting"),
+        0
+    );
+}
+
+#[test]
+fn stash_apply() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.write("file.txt", "staged\n");
+    repo.git(&["add", "file.txt"]);
+    GitLib::stash_push(None, false, false, Some(repo.path())).expect("Error stashing");
+
+    // Without restore_index, the change comes back unstaged
+    GitLib::stash_apply(Some(0), false, Some(repo.path())).expect("Error applying");
+    assert_eq!(repo.git(&["diff", "--cached", "--name-only"]), "");
+    assert_eq!(repo.git(&["diff", "--name-only"]), "file.txt\n");
+    assert_eq!(
+        GitLib::stash_count(Some(repo.path())).expect("Error counting"),
+        1
+    );
+}
+
+#[test]
+fn blame_author_email() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "one\n", "First commit");
+    repo.git(&["config", "user.email", "other@example.com"]);
+    repo.commit_file("file.txt", "one\ntwo\n", "Second commit");
+
+    let lines = GitLib::blame("file.txt", Some(repo.path())).expect("Error blaming");
+    let emails: Vec<_> = lines
+        .iter()
+        .map(|line| line.author_email().clone())
+        .collect();
+    assert_eq!(
+        emails,
+        vec![
+            Some("test@example.com".to_owned()),
+            Some("other@example.com".to_owned())
+        ]
+    );
+}
+
+#[test]
+fn worktree_repair() {
+    let dir = test_repo::TestRepo::empty_dir();
+    dir.git(&["init", "-q", "-b", "main", "main"]);
+    dir.git(&[
+        "-C",
+        "main",
+        "-c",
+        "user.name=Test User",
+        "-c",
+        "user.email=test@example.com",
+        "commit",
+        "-q",
+        "--allow-empty",
+        "-m",
+        "First commit",
+    ]);
+    dir.git(&[
+        "-C",
+        "main",
+        "worktree",
+        "add",
+        "-q",
+        "../linked",
+        "-b",
+        "linked",
+    ]);
+    let moved = dir.path().join("moved");
+    let linked = dir.path().join("linked");
+    std::fs::rename(dir.path().join("main"), &moved).expect("Error moving repo");
+    dir.git_fails(&["-C", "linked", "status"]);
+
+    GitLib::worktree_repair(&[&linked], Some(&moved)).expect("Error repairing worktree");
+    dir.git(&["-C", "linked", "status"]);
+    let worktrees = GitLib::worktree_list(Some(&moved)).expect("Error listing worktrees");
+    assert_eq!(worktrees[1].path(), &linked);
+}
+
+#[cfg(unix)]
+#[test]
+fn list_hooks() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = test_repo::TestRepo::new();
+    let hooks_path = GitLib::hooks_path(Some(repo.path())).expect("Error getting hooks path");
+    assert_eq!(hooks_path, repo.path().join(".git/hooks"));
+
+    std::fs::create_dir_all(&hooks_path).expect("Error creating hooks folder");
+    for name in ["pre-commit", "commit-msg.sample"] {
+        let hook = hooks_path.join(name);
+        std::fs::write(&hook, "#!/bin/sh\nexit 0\n").expect("Error writing hook");
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))
+            .expect("Error making hook executable");
+    }
+    std::fs::write(hooks_path.join("not-executable"), "").expect("Error writing file");
+    assert_eq!(
+        GitLib::list_hooks(Some(repo.path())).expect("Error listing hooks"),
+        vec!["pre-commit"]
+    );
+
+    repo.git(&["config", "core.hooksPath", "custom-hooks"]);
+    assert_eq!(
+        GitLib::hooks_path(Some(repo.path())).expect("Error getting hooks path"),
+        repo.path().join("custom-hooks")
+    );
+    assert!(GitLib::list_hooks(Some(repo.path()))
+        .expect("Error listing hooks")
+        .is_empty());
+}
+
+// Git only lists vimdiff as available if Vim is installed
+#[test]
+#[ignore = "requires Vim"]
+fn list_tools() {
+    let repo = test_repo::TestRepo::new();
+    let merge_tools = GitLib::list_merge_tools(Some(repo.path())).expect("Error listing tools");
+    assert!(merge_tools.iter().any(|tool| tool == "vimdiff"));
+    assert!(!merge_tools.iter().any(|tool| tool == "araxis"));
+    let diff_tools = GitLib::list_diff_tools(Some(repo.path())).expect("Error listing tools");
+    assert!(diff_tools.iter().any(|tool| tool == "vimdiff"));
+}
+
+#[test]
+fn count_between() {
+    let repo = test_repo::TestRepo::new();
+    let fork = repo.commit_file("file.txt", "base\n", "Base commit");
+    repo.git(&["checkout", "-q", "-b", "feature"]);
+    repo.commit_file("file.txt", "one\n", "First feature commit");
+    repo.commit_file("file.txt", "two\n", "Second feature commit");
+
+    let count = GitLib::count_between(&fork, "feature", Some(repo.path())).expect("Error counting");
+    assert_eq!(count, 2);
+    let count =
+        GitLib::count_between("feature", "main", Some(repo.path())).expect("Error counting");
+    assert_eq!(count, 0);
+    assert!(GitLib::count_between("main", "no-such-branch", Some(repo.path())).is_err());
+}
+
+#[test]
+fn empty_tree_oid() {
+    let repo = test_repo::TestRepo::new();
+    let oid = GitLib::empty_tree_oid(Some(repo.path())).expect("Error getting empty tree");
+    assert_eq!(oid, "4b825dc642cb6eb9a060e54bf8d69288fbee4904");
+    assert_eq!(repo.git(&["cat-file", "-t", oid.as_str()]), "tree\n");
+    assert_eq!(repo.git(&["ls-tree", oid.as_str()]), "");
+    // Nothing is written to the repository
+    assert!(!repo
+        .path()
+        .join(".git/objects/4b/825dc642cb6eb9a060e54bf8d69288fbee4904")
+        .exists());
+
+    let sha256 = test_repo::TestRepo::empty_dir();
+    sha256.git(&["init", "-q", "--object-format=sha256"]);
+    let oid = GitLib::empty_tree_oid(Some(sha256.path())).expect("Error getting empty tree");
+    assert_eq!(oid.len(), 64);
+    assert_eq!(sha256.git(&["cat-file", "-t", oid.as_str()]), "tree\n");
+}
+
+#[test]
+fn config_path() {
+    let repo = test_repo::TestRepo::new();
+    let config = GitLib::config_path(ConfigScope::Local, Some(repo.path()))
+        .expect("Error getting config path");
+    assert_eq!(config, repo.path().join(".git/config"));
+}
+
+#[test]
+fn config_path_global() {
+    let repo = test_repo::TestRepo::new();
+    let home = test_repo::TestRepo::empty_dir();
+    let file = home.path().join("global config");
+    let config = GitConfig::new().with_env("GIT_CONFIG_GLOBAL", file.display());
+    let config_path = |config: &GitConfig| {
+        GitLib::config_path_with(config, ConfigScope::Global, Some(repo.path()))
+            .expect("Error getting config path")
+    };
+    // Missing, empty, and with settings
+    assert_eq!(config_path(&config), file);
+    std::fs::write(&file, "").expect("Error writing config");
+    assert_eq!(config_path(&config), file);
+    assert_eq!(
+        std::fs::read_to_string(&file).expect("Error reading config"),
+        ""
+    );
+    std::fs::write(&file, "[test]\n\tkey = value\n").expect("Error writing config");
+    assert_eq!(config_path(&config), file);
+
+    let xdg = home.path().join("xdg");
+    let config = GitConfig::new()
+        .with_env("HOME", home.path().display())
+        .with_env("XDG_CONFIG_HOME", xdg.display());
+    assert_eq!(config_path(&config), home.path().join(".gitconfig"));
+    // The XDG file is used if it's the only one
+    std::fs::create_dir_all(xdg.join("git")).expect("Error creating XDG folder");
+    std::fs::write(xdg.join("git/config"), "").expect("Error writing XDG config");
+    assert_eq!(config_path(&config), xdg.join("git/config"));
+    std::fs::write(home.path().join(".gitconfig"), "").expect("Error writing config");
+    assert_eq!(config_path(&config), home.path().join(".gitconfig"));
+}
+
+#[test]
+fn config_path_system() {
+    let repo = test_repo::TestRepo::new();
+    let dir = test_repo::TestRepo::empty_dir();
+    let file = dir.path().join("system config");
+    let config = GitConfig::new().with_env("GIT_CONFIG_SYSTEM", file.display());
+    let config_path = |config: &GitConfig| {
+        GitLib::config_path_with(config, ConfigScope::System, Some(repo.path()))
+            .expect("Error getting config path")
+    };
+    // Missing, empty, and with settings
+    assert_eq!(config_path(&config), file);
+    std::fs::write(&file, "").expect("Error writing config");
+    assert_eq!(config_path(&config), file);
+    std::fs::write(&file, "[test]\n\tkey = value\n").expect("Error writing config");
+    assert_eq!(config_path(&config), file);
+
+    // Without the variable, it's wherever Git reads its system settings from
+    let default = GitLib::config_path(ConfigScope::System, Some(repo.path()))
+        .expect("Error getting config path");
+    assert!(default.is_absolute());
+    let origins = repo.git(&["config", "--system", "--list", "--show-origin", "-z"]);
+    if let Some(origin) = origins
+        .split('\0')
+        .next()
+        .filter(|origin| !origin.is_empty())
+    {
+        assert_eq!(origin, format!("file:{}", default.display()));
+    }
+}
+
+#[test]
+fn rev_parse_verify() {
+    let repo = test_repo::TestRepo::new();
+    let sha = repo.commit_file("file.txt", "contents\n", "First commit");
+    assert_eq!(
+        GitLib::rev_parse_verify("main", Some(repo.path())).expect("Error parsing revision"),
+        Some(sha)
+    );
+    assert_eq!(
+        GitLib::rev_parse_verify("no-such-branch", Some(repo.path()))
+            .expect("Error parsing revision"),
+        None
+    );
+}
+
+#[test]
+fn abbreviate() {
+    let repo = test_repo::TestRepo::new();
+    let sha = repo.commit_file("file.txt", "contents\n", "First commit");
+
+    let short = GitLib::abbreviate(&sha, None, Some(repo.path())).expect("Error abbreviating");
+    assert!(short.len() < sha.len());
+    assert!(sha.starts_with(&short));
+    assert_eq!(
+        GitLib::rev_parse_verify(&short, Some(repo.path())).expect("Error parsing revision"),
+        Some(sha.clone())
+    );
+
+    let short = GitLib::abbreviate(&sha, Some(12), Some(repo.path())).expect("Error abbreviating");
+    assert_eq!(short, sha[..12]);
+}
+
+#[test]
+fn tracking_branch() {
+    let origin = test_repo::TestRepo::new();
+    origin.commit_file("file.txt", "contents\n", "First commit");
+    origin.git(&["branch", "feature"]);
+    let repo = test_repo::TestRepo::clone_of(&origin);
+    repo.git(&["branch", "-q", "--track", "feature", "origin/feature"]);
+    repo.git(&["branch", "local"]);
+
+    assert_eq!(
+        GitLib::tracking_branch("feature", Some(repo.path())).expect("Error getting upstream"),
+        Some("origin/feature".to_owned())
+    );
+    assert_eq!(
+        GitLib::tracking_branch("local", Some(repo.path())).expect("Error getting upstream"),
+        None
+    );
+    assert!(GitLib::tracking_branch("no-such-branch", Some(repo.path())).is_err());
+}
+
+#[test]
+fn credentials_fill_nonblocking() {
+    // Nothing has credentials for this URL, so filling them would mean prompting
+    let result = GitLib::credentials_fill_nonblocking("https://git-lib-test.invalid/repo.git");
+    assert!(result.is_err());
+}
+
+#[test]
+fn stash_files() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("one.txt", "one\n", "First commit");
+    repo.commit_file("two.txt", "two\n", "Second commit");
+    repo.write("one.txt", "changed\n");
+    repo.write("two.txt", "changed\n");
+    GitLib::stash_push(None, false, false, Some(repo.path())).expect("Error stashing");
+
+    assert_eq!(
+        GitLib::stash_files(0, Some(repo.path())).expect("Error listing stash files"),
+        vec![PathBuf::from("one.txt"), PathBuf::from("two.txt")]
+    );
+    assert!(GitLib::stash_files(1, Some(repo.path())).is_err());
+}
+
+#[test]
+fn diff_check() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "one\ntwo\n", "First commit");
+    assert!(GitLib::diff_check(Some(repo.path()))
+        .expect("Error checking diff")
+        .is_empty());
+
+    repo.write("file.txt", "one\ntwo  \n");
+    let issues = GitLib::diff_check(Some(repo.path())).expect("Error checking diff");
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].file(), &PathBuf::from("file.txt"));
+    assert_eq!(issues[0].line(), 2);
+    assert_eq!(issues[0].message(), "trailing whitespace.");
+
+    let dir = test_repo::TestRepo::empty_dir();
+    assert!(GitLib::diff_check(Some(dir.path())).is_err());
+}
+
+#[test]
+fn staged_files() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.write("file.txt", "changed\n");
+    assert!(GitLib::staged_files(Some(repo.path()))
+        .expect("Error listing staged files")
+        .is_empty());
+
+    repo.write("new file.txt", "new\n");
+    repo.git(&["add", "new file.txt"]);
+    assert_eq!(
+        GitLib::staged_files(Some(repo.path())).expect("Error listing staged files"),
+        vec![PathBuf::from("new file.txt")]
+    );
+}
+
+#[test]
+fn unmerged_files() {
+    let repo = test_repo::TestRepo::with_conflicting_branch();
+    repo.commit_file("other.txt", "other\n", "Other commit");
+    assert!(GitLib::unmerged_files(Some(repo.path()))
+        .expect("Error listing unmerged files")
+        .is_empty());
+
+    repo.git_fails(&["merge", "feature"]);
+    assert_eq!(
+        GitLib::unmerged_files(Some(repo.path())).expect("Error listing unmerged files"),
+        vec![PathBuf::from("file.txt")]
+    );
+}
+
+#[test]
+fn worktree_add_detached() {
+    let repo = test_repo::TestRepo::new();
+    let first = repo.commit_file("file.txt", "one\n", "First commit");
+    repo.commit_file("file.txt", "two\n", "Second commit");
+    let dir = test_repo::TestRepo::empty_dir();
+    let linked = dir.path().join("linked");
+
+    assert!(
+        GitLib::worktree_add(&linked, Some("HEAD~1"), Some("b"), true, Some(repo.path())).is_err()
+    );
+    GitLib::worktree_add(&linked, Some("HEAD~1"), None, true, Some(repo.path()))
+        .expect("Error adding worktree");
+    let worktrees = GitLib::worktree_list(Some(repo.path())).expect("Error listing worktrees");
+    assert_eq!(worktrees[1].path(), &linked);
+    assert!(worktrees[1].detached());
+    assert_eq!(worktrees[1].head(), &Some(first));
+}
+
+#[test]
+fn config_set_if_absent() {
+    let repo = test_repo::TestRepo::new();
+    assert!(GitLib::config_set_if_absent(
+        "test.key",
+        "default",
+        ConfigScope::Local,
+        Some(repo.path())
+    )
+    .expect("Error setting config"));
+    assert_eq!(repo.git(&["config", "--local", "test.key"]), "default\n");
+
+    GitLib::config_set("test.key", "mine", ConfigScope::Local, Some(repo.path()))
+        .expect("Error setting config");
+    assert!(!GitLib::config_set_if_absent(
+        "test.key",
+        "default",
+        ConfigScope::Local,
+        Some(repo.path())
+    )
+    .expect("Error setting config"));
+    assert_eq!(
+        GitLib::config_get("test.key", Some(ConfigScope::Local), Some(repo.path()))
+            .expect("Error getting config"),
+        Some("mine".to_owned())
+    );
+}
+
+#[test]
+fn commit_count_by_author() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "one\n", "First commit");
+    repo.git(&["config", "user.name", "Other User"]);
+    repo.git(&["config", "user.email", "other@example.com"]);
+    repo.commit_file("file.txt", "two\n", "Second commit");
+    repo.commit_file("file.txt", "three\n", "Third commit");
+
+    assert_eq!(
+        GitLib::commit_count_by_author(Some(repo.path())).expect("Error counting commits"),
+        vec![
+            ("Other User".to_owned(), "other@example.com".to_owned(), 2),
+            ("Test User".to_owned(), "test@example.com".to_owned(), 1),
+        ]
+    );
+}
+
+#[test]
+fn commit_count_by_author_no_commits() {
+    let repo = test_repo::TestRepo::new();
+    match GitLib::commit_count_by_author(Some(repo.path())) {
+        Ok(counts) => panic!("Unexpected counts {counts:?}"),
+        Err(error) => assert_eq!(error.kind(), ErrorKind::NoCommits),
+    }
+}
+
+#[test]
+fn file_mode() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.write("script.sh", "#!/bin/sh\n");
+    repo.git(&["add", "--chmod=+x", "script.sh"]);
+
+    assert_eq!(
+        GitLib::file_mode("file.txt", Some(repo.path())).expect("Error getting mode"),
+        "100644"
+    );
+    assert_eq!(
+        GitLib::file_mode("script.sh", Some(repo.path())).expect("Error getting mode"),
+        "100755"
+    );
+    assert!(GitLib::file_mode("untracked.txt", Some(repo.path())).is_err());
+}
+
+#[test]
+fn file_mode_not_a_file() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("dir/file.txt", "contents\n", "First commit");
+
+    assert!(GitLib::file_mode("dir", Some(repo.path())).is_err());
+    assert!(GitLib::file_mode("dir/*.txt", Some(repo.path())).is_err());
+    assert_eq!(
+        GitLib::file_mode("dir/file.txt", Some(repo.path())).expect("Error getting mode"),
+        "100644"
+    );
+}
+
+#[test]
+fn set_executable() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("script.sh", "#!/bin/sh\n", "First commit");
+
+    GitLib::set_executable("script.sh", true, Some(repo.path())).expect("Error setting mode");
+    assert_eq!(
+        GitLib::file_mode("script.sh", Some(repo.path())).expect("Error getting mode"),
+        "100755"
+    );
+    GitLib::set_executable("script.sh", false, Some(repo.path())).expect("Error setting mode");
+    assert_eq!(
+        GitLib::file_mode("script.sh", Some(repo.path())).expect("Error getting mode"),
+        "100644"
+    );
+}
+
+#[test]
+fn tracked_size() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("one.txt", "12345\n", "First commit");
+    repo.write("dir/two.txt", "1234567890\n");
+    repo.git(&["add", "."]);
+    repo.git(&["commit", "-q", "-m", "Second commit"]);
+    // Untracked files don't count
+    repo.write("untracked.txt", "untracked\n");
+
+    assert_eq!(
+        GitLib::tracked_size(Some(repo.path())).expect("Error getting size"),
+        6 + 11
+    );
+}
+
+#[test]
+fn is_binary() {
+    let repo = test_repo::TestRepo::new();
+    repo.write("file.txt", "text\n");
+    std::fs::write(
+        repo.path().join("image.png"),
+        b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR",
+    )
+    .expect("Error writing image");
+    repo.write("data.dat", "looks like text\n");
+    repo.write(".gitattributes", "*.dat binary\n");
+    repo.git(&["add", "."]);
+    repo.git(&["commit", "-q", "-m", "First commit"]);
+
+    let is_binary =
+        |file, rev| GitLib::is_binary(file, rev, Some(repo.path())).expect("Error checking file");
+    assert!(!is_binary("file.txt", None));
+    assert!(!is_binary("file.txt", Some("HEAD")));
+    assert!(is_binary("image.png", None));
+    assert!(is_binary("image.png", Some("HEAD")));
+    assert!(is_binary("data.dat", Some("HEAD")));
+    assert!(GitLib::is_binary("missing.txt", Some("HEAD"), Some(repo.path())).is_err());
+}
+
+#[test]
+fn refs() {
+    let repo = test_repo::TestRepo::new();
+    let sha = repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.git(&["branch", "feature"]);
+    repo.git(&["tag", "v1.0"]);
+
+    let heads = GitLib::refs(RefKind::Heads, Some(repo.path())).expect("Error listing refs");
+    let names: Vec<&str> = heads.iter().map(|head| head.name()).collect();
+    assert_eq!(names, vec!["refs/heads/feature", "refs/heads/main"]);
+    assert!(heads.iter().all(|head| head.oid() == sha));
+
+    let tags = GitLib::refs(RefKind::Tags, Some(repo.path())).expect("Error listing refs");
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].name(), "refs/tags/v1.0");
+    assert_eq!(tags[0].oid(), sha);
+
+    assert!(GitLib::refs(RefKind::Remotes, Some(repo.path()))
+        .expect("Error listing refs")
+        .is_empty());
+    assert_eq!(
+        GitLib::refs(RefKind::All, Some(repo.path()))
+            .expect("Error listing refs")
+            .len(),
+        3
+    );
+}
+
+#[test]
+fn diff_name_status() {
+    let repo = test_repo::TestRepo::new();
+    repo.write("modified.txt", "one\n");
+    repo.write("deleted.txt", "deleted\n");
+    repo.write("old.txt", "a file that will be renamed\n");
+    repo.git(&["add", "."]);
+    repo.git(&["commit", "-q", "-m", "First commit"]);
+    repo.write("modified.txt", "two\n");
+    repo.write("added.txt", "added\n");
+    std::fs::remove_file(repo.path().join("deleted.txt")).expect("Error deleting file");
+    repo.git(&["mv", "old.txt", "new.txt"]);
+    repo.git(&["add", "-A"]);
+    repo.git(&["commit", "-q", "-m", "Second commit"]);
+
+    let changes = GitLib::diff_name_status(Some("HEAD~1"), Some("HEAD"), Some(repo.path()))
+        .expect("Error diffing");
+    assert_eq!(
+        changes,
+        vec![
+            (ChangeKind::Added, PathBuf::from("added.txt")),
+            (ChangeKind::Deleted, PathBuf::from("deleted.txt")),
+            (ChangeKind::Modified, PathBuf::from("modified.txt")),
+            (
+                ChangeKind::Renamed(PathBuf::from("old.txt")),
+                PathBuf::from("new.txt")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn diff_name_status_to_only() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "one\n", "First commit");
+    repo.git(&["branch", "other"]);
+    repo.commit_file("added.txt", "added\n", "Second commit");
+    // Changes in the working tree shouldn't be included
+    repo.write("file.txt", "two\n");
+
+    let changes =
+        GitLib::diff_name_status(None, Some("other"), Some(repo.path())).expect("Error diffing");
+    assert_eq!(
+        changes,
+        vec![(ChangeKind::Deleted, PathBuf::from("added.txt"))]
+    );
+}
+
+#[test]
+fn diff_name_status_unmerged() {
+    let repo = test_repo::TestRepo::with_conflicting_branch();
+    repo.git_fails(&["merge", "feature"]);
+
+    let changes = GitLib::diff_name_status(None, None, Some(repo.path())).expect("Error diffing");
+    assert!(changes.contains(&(ChangeKind::Unmerged, PathBuf::from("file.txt"))));
+}
+
+#[test]
+fn submodule_status() {
+    let sub = test_repo::TestRepo::new();
+    let recorded = sub.commit_file("file.txt", "one\n", "First commit");
+    let repo = test_repo::TestRepo::new();
+    repo.add_submodule(&sub, "lib dir");
+
+    let statuses = GitLib::submodule_status(Some(repo.path())).expect("Error getting status");
+    assert_eq!(statuses.len(), 1);
+    assert_eq!(statuses[0].path(), &PathBuf::from("lib dir"));
+    assert_eq!(statuses[0].sha(), recorded);
+    assert_eq!(statuses[0].state(), SubmoduleState::Clean);
+
+    let advanced = sub.commit_file("file.txt", "two\n", "Second commit");
+    repo.git(&["-C", "lib dir", "fetch", "-q"]);
+    repo.git(&["-C", "lib dir", "checkout", "-q", "FETCH_HEAD"]);
+    let statuses = GitLib::submodule_status(Some(repo.path())).expect("Error getting status");
+    assert_eq!(statuses[0].sha(), advanced);
+    assert_eq!(statuses[0].state(), SubmoduleState::OutOfDate);
+}
+
+#[test]
+fn submodule_foreach() {
+    let sub = test_repo::TestRepo::new();
+    sub.commit_file("file.txt", "contents\n", "First commit");
+    let repo = test_repo::TestRepo::new();
+    repo.add_submodule(&sub, "one");
+    repo.add_submodule(&sub, "two");
+
+    let output = GitLib::submodule_foreach(&["echo", "hello;", "world"], false, Some(repo.path()))
+        .expect("Error running command");
+    assert_eq!(
+        output,
+        "Entering 'one'\nhello; world\nEntering 'two'\nhello; world\n"
+    );
+    assert!(GitLib::submodule_foreach(&[], false, Some(repo.path())).is_err());
+}
+
+#[test]
+fn submodule_foreach_quoting() {
+    let sub = test_repo::TestRepo::new();
+    sub.commit_file("file.txt", "contents\n", "First commit");
+    let repo = test_repo::TestRepo::new();
+    repo.add_submodule(&sub, "one");
+
+    let output = GitLib::submodule_foreach(
+        &["echo", "$name; touch 'injected' `touch injected`"],
+        false,
+        Some(repo.path()),
+    )
+    .expect("Error running command");
+    assert_eq!(
+        output,
+        "Entering 'one'\n$name; touch 'injected' `touch injected`\n"
+    );
+    assert!(!repo.path().join("one/injected").exists());
+
+    // A single element is a program name, not a shell command
+    assert!(
+        GitLib::submodule_foreach(&["true; touch injected"], false, Some(repo.path())).is_err()
+    );
+    assert!(!repo.path().join("one/injected").exists());
+}
+
+#[test]
+fn stash_pop_conflict() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "base\n", "First commit");
+    repo.write("file.txt", "stashed\n");
+    GitLib::stash_push(None, false, false, Some(repo.path())).expect("Error stashing");
+    repo.commit_file("file.txt", "committed\n", "Second commit");
+
+    assert_eq!(
+        GitLib::stash_pop(None, false, Some(repo.path())).expect("Error popping"),
+        StashOutcome::Conflict(vec![PathBuf::from("file.txt")])
+    );
+    // The stash is kept when it doesn't apply cleanly
+    assert_eq!(
+        GitLib::stash_count(Some(repo.path())).expect("Error counting"),
+        1
+    );
+}
+
+#[test]
+fn remote_branches() {
+    let origin = test_repo::TestRepo::new();
+    origin.commit_file("file.txt", "contents\n", "First commit");
+    origin.git(&["branch", "feature/one"]);
+    let repo = test_repo::TestRepo::clone_of(&origin);
+    GitLib::remote_add("other", origin.path_str(), Some(repo.path())).expect("Error adding remote");
+
+    assert_eq!(
+        GitLib::remote_branches("origin", Some(repo.path())).expect("Error listing branches"),
+        vec!["feature/one", "main"]
+    );
+    assert!(GitLib::remote_branches("other", Some(repo.path()))
+        .expect("Error listing branches")
+        .is_empty());
+}
+
+#[test]
+fn config_includes() {
+    let repo = test_repo::TestRepo::new();
+    let dir = test_repo::TestRepo::empty_dir();
+    let absolute = dir.path().join("absolute.inc");
+    std::fs::write(&absolute, "[test]\n\tabsolute = 1\n").expect("Error writing include");
+    repo.write(".git/relative.inc", "[test]\n\trelative = 1\n");
+    repo.git(&[
+        "config",
+        "--add",
+        "include.path",
+        absolute.to_str().unwrap(),
+    ]);
+    repo.git(&["config", "--add", "include.path", "relative.inc"]);
+    // Not reported, because it doesn't set anything
+    repo.write(".git/empty.inc", "");
+    repo.git(&["config", "--add", "include.path", "empty.inc"]);
+
+    assert_eq!(
+        GitLib::config_includes(Some(repo.path())).expect("Error listing includes"),
+        vec![absolute, repo.path().join(".git/relative.inc")]
+    );
+}
+
+#[test]
+fn signed_tags_unsigned() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.git(&["tag", "lightweight"]);
+    repo.git(&["tag", "-a", "-m", "Annotated", "annotated"]);
+
+    assert_eq!(
+        GitLib::signed_tags(Some(repo.path())).expect("Error verifying tags"),
+        vec![
+            ("annotated".to_owned(), SignatureStatus::None),
+            ("lightweight".to_owned(), SignatureStatus::None),
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+#[ignore = "requires gpg"]
+fn signed_tags() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::Command;
+
+    // Use a throwaway keyring, by way of a wrapper that Git runs instead of gpg
+    let gnupg = test_repo::TestRepo::empty_dir();
+    std::fs::set_permissions(gnupg.path(), std::fs::Permissions::from_mode(0o700))
+        .expect("Error securing keyring");
+    let keyring = gnupg.path().join("keyring");
+    std::fs::create_dir(&keyring).expect("Error creating keyring");
+    std::fs::set_permissions(&keyring, std::fs::Permissions::from_mode(0o700))
+        .expect("Error securing keyring");
+    let wrapper = gnupg.path().join("gpg-wrapper");
+    std::fs::write(
+        &wrapper,
+        format!(
+            "#!/bin/sh\nGNUPGHOME='{}' exec gpg \"$@\"\n",
+            keyring.display()
+        ),
+    )
+    .expect("Error writing wrapper");
+    std::fs::set_permissions(&wrapper, std::fs::Permissions::from_mode(0o755))
+        .expect("Error making wrapper executable");
+    let generated = Command::new(&wrapper)
+        .args([
+            "--batch",
+            "--passphrase",
+            "",
+            "--quick-gen-key",
+            "Test User <test@example.com>",
+            "ed25519",
+            "sign",
+            "never",
+        ])
+        .output()
+        .expect("Error running gpg");
+    assert!(generated.status.success(), "Error generating key");
+
+    let repo = test_repo::TestRepo::new();
+    repo.git(&["config", "gpg.program", wrapper.to_str().unwrap()]);
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    repo.git(&["tag", "-s", "-m", "Signed", "signed"]);
+    repo.git(&["tag", "-a", "-m", "Unsigned", "unsigned"]);
+
+    assert_eq!(
+        GitLib::signed_tags(Some(repo.path())).expect("Error verifying tags"),
+        vec![
+            ("signed".to_owned(), SignatureStatus::Good),
+            ("unsigned".to_owned(), SignatureStatus::None),
+        ]
+    );
+    // Stop the agent GPG started for the keyring
+    let _ = Command::new("gpgconf")
+        .args(["--kill", "gpg-agent"])
+        .env("GNUPGHOME", &keyring)
+        .output();
+}
+
+#[test]
+fn worktree_of() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file("file.txt", "contents\n", "First commit");
+    // Nested inside the main working tree
+    let linked = repo.path().join("linked");
+    GitLib::worktree_add(&linked, None, Some("linked"), false, Some(repo.path()))
+        .expect("Error adding worktree");
+
+    let worktree = GitLib::worktree_of(&linked.join("file.txt"), Some(repo.path()))
+        .expect("Error finding worktree")
+        .expect("No worktree found");
+    assert_eq!(worktree.path(), &linked);
+    assert_eq!(worktree.branch(), &Some("refs/heads/linked".to_owned()));
+
+    let worktree = GitLib::worktree_of(&repo.path().join("file.txt"), Some(repo.path()))
+        .expect("Error finding worktree")
+        .expect("No worktree found");
+    assert_eq!(worktree.path(), repo.path());
+
+    let outside = test_repo::TestRepo::empty_dir();
+    assert_eq!(
+        GitLib::worktree_of(outside.path(), Some(repo.path())).expect("Error finding worktree"),
+        None
+    );
+}
+
+#[test]
+fn commit_allow_empty() {
+    let repo = test_repo::TestRepo::new();
+    let first = repo.commit_file("file.txt", "contents\n", "First commit");
+    assert!(GitLib::commit("Nothing", None, None, false, Some(repo.path())).is_err());
+
+    let sha =
+        GitLib::commit("Marker", None, None, true, Some(repo.path())).expect("Error committing");
+    assert_eq!(
+        GitLib::rev_list("HEAD", None, Some(repo.path())).expect("Error listing commits"),
+        vec![sha.clone(), first]
+    );
+    assert_eq!(
+        GitLib::commit_message(&sha, Some(repo.path())).expect("Error getting message"),
+        "Marker\n"
+    );
+}
+
+#[test]
+fn default_remote() {
+    let origin = test_repo::TestRepo::new();
+    origin.commit_file("file.txt", "contents\n", "First commit");
+    let repo = test_repo::TestRepo::clone_of(&origin);
+    GitLib::remote_add("upstream", origin.path_str(), Some(repo.path()))
+        .expect("Error adding remote");
+    repo.git(&["config", "branch.main.remote", "upstream"]);
+    assert_eq!(
+        GitLib::remotes(Some(repo.path())).expect("Error listing remotes"),
+        vec!["origin", "upstream"]
+    );
+    assert_eq!(
+        GitLib::default_remote(Some(repo.path())).expect("Error getting remote"),
+        Some("upstream".to_owned())
+    );
+    // Without a configured remote, there are two to choose from
+    repo.git(&["checkout", "-q", "-b", "local"]);
+    assert_eq!(
+        GitLib::default_remote(Some(repo.path())).expect("Error getting remote"),
+        None
+    );
+}
+
+#[test]
+fn default_remote_single() {
+    let origin = test_repo::TestRepo::new();
+    origin.commit_file("file.txt", "contents\n", "First commit");
+    let repo = test_repo::TestRepo::clone_of(&origin);
+    repo.git(&["checkout", "-q", "-b", "local"]);
+    assert_eq!(
+        GitLib::default_remote(Some(repo.path())).expect("Error getting remote"),
+        Some("origin".to_owned())
+    );
+}
+
+#[test]
+fn ignored_files() {
+    let repo = test_repo::TestRepo::new();
+    repo.commit_file(".gitignore", "*.log\nbuild/\n", "First commit");
+    repo.write("debug.log", "log\n");
+    repo.write("build/output.bin", "output\n");
+    repo.write("untracked.txt", "untracked\n");
+
+    assert_eq!(
+        GitLib::ignored_files(Some(repo.path())).expect("Error listing ignored files"),
+        vec![PathBuf::from("build/"), PathBuf::from("debug.log")]
+    );
+}
+
+#[test]
+fn fetch_ref() {
+    let source = test_repo::TestRepo::new();
+    let sha = source.commit_file("file.txt", "contents\n", "First commit");
+    let remote = test_repo::TestRepo::bare();
+    source.git(&["push", "-q", remote.path_str(), "main:refs/pull/42/head"]);
+    let repo = test_repo::TestRepo::new();
+    GitLib::remote_add("origin", remote.path_str(), Some(repo.path()))
+        .expect("Error adding remote");
+
+    GitLib::fetch_ref(
+        "origin",
+        "refs/pull/42/head",
+        "refs/heads/pr-42",
+        Some(repo.path()),
+    )
+    .expect("Error fetching ref");
+    assert_eq!(repo.git(&["rev-parse", "pr-42"]), format!("{sha}\n"));
+
+    match GitLib::fetch_ref(
+        "origin",
+        "refs/pull/43/head",
+        "refs/heads/pr-43",
+        Some(repo.path()),
+    ) {
+        Ok(_) => panic!("Fetched a missing ref"),
+        Err(error) => assert_eq!(
+            error.message(),
+            "origin doesn't have a ref named refs/pull/43/head"
+        ),
     }
 }
+
+#[test]
+fn show_file_at_rev_to_writer() {
+    let repo = test_repo::TestRepo::new();
+    let contents: String = (0..10_000).map(|line| format!("Line {line}\n")).collect();
+    repo.commit_file("big.txt", &contents, "First commit");
+    repo.commit_file("big.txt", "replaced\n", "Second commit");
+
+    let mut streamed: Vec<u8> = vec![];
+    let written =
+        GitLib::show_file_at_rev_to_writer("HEAD~1", "big.txt", &mut streamed, Some(repo.path()))
+            .expect("Error streaming file");
+    assert_eq!(written, contents.len() as u64);
+    assert_eq!(streamed, contents.as_bytes());
+    assert_eq!(
+        GitLib::show_file_at_rev("HEAD~1", "big.txt", Some(repo.path()))
+            .expect("Error showing file"),
+        streamed
+    );
+
+    let mut streamed: Vec<u8> = vec![];
+    assert!(GitLib::show_file_at_rev_to_writer(
+        "HEAD",
+        "missing.txt",
+        &mut streamed,
+        Some(repo.path())
+    )
+    .is_err());
+}
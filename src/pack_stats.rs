@@ -0,0 +1,77 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+use std::collections::BTreeMap;
+
+/// Statistics about a pack file, as reported by `git verify-pack -v`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PackStats {
+    total_objects: u64,
+    non_delta_objects: u64,
+    chain_lengths: BTreeMap<u32, u64>,
+    size: u64,
+    size_in_pack: u64,
+}
+
+impl PackStats {
+    pub fn total_objects(&self) -> u64 {
+        self.total_objects
+    }
+    /// The number of objects stored whole rather than as deltas
+    pub fn non_delta_objects(&self) -> u64 {
+        self.non_delta_objects
+    }
+    /// The number of deltified objects for each delta chain length
+    pub fn chain_lengths(&self) -> &BTreeMap<u32, u64> {
+        &self.chain_lengths
+    }
+    /// The total size of the objects, uncompressed
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+    /// The total space the objects take up in the pack
+    pub fn size_in_pack(&self) -> u64 {
+        self.size_in_pack
+    }
+
+    /// Parses the output of `git verify-pack -v`. Each object has a line of the form
+    /// `<oid> <type> <size> <size-in-pack> <offset> [<depth> <base-oid>]`, and a histogram of
+    /// `non delta: <n> objects` and `chain length = <length>: <n> objects` lines follows them.
+    pub(crate) fn from_verify_pack(output: &str) -> PackStats {
+        fn object_count(count: &str) -> Option<u64> {
+            count
+                .trim()
+                .trim_end_matches(" objects")
+                .trim_end_matches(" object")
+                .parse()
+                .ok()
+        }
+
+        let mut stats = PackStats::default();
+        for line in output.lines() {
+            if let Some(count) = line.strip_prefix("non delta:") {
+                stats.non_delta_objects = object_count(count).unwrap_or_default();
+            } else if let Some(histogram) = line.strip_prefix("chain length = ") {
+                if let Some((length, count)) = histogram.split_once(':') {
+                    if let (Ok(length), Some(count)) = (length.parse(), object_count(count)) {
+                        stats.chain_lengths.insert(length, count);
+                    }
+                }
+            } else {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.len() >= 5 {
+                    if let (Ok(size), Ok(size_in_pack)) =
+                        (fields[2].parse::<u64>(), fields[3].parse::<u64>())
+                    {
+                        stats.total_objects += 1;
+                        stats.size += size;
+                        stats.size_in_pack += size_in_pack;
+                    }
+                }
+            }
+        }
+        stats
+    }
+}
@@ -0,0 +1,29 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+/// Which refs to list
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefKind {
+    /// Local branches, under `refs/heads`
+    Heads,
+    /// Tags, under `refs/tags`
+    Tags,
+    /// Remote-tracking branches, under `refs/remotes`
+    Remotes,
+    /// Every ref, including the above and things like notes and stashes
+    All,
+}
+
+impl RefKind {
+    /// The pattern that `git for-each-ref` uses to select the refs, if any
+    pub(crate) fn pattern(&self) -> Option<&'static str> {
+        match self {
+            RefKind::Heads => Some("refs/heads"),
+            RefKind::Tags => Some("refs/tags"),
+            RefKind::Remotes => Some("refs/remotes"),
+            RefKind::All => None,
+        }
+    }
+}
@@ -0,0 +1,43 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+use std::path::Path;
+
+/// The multi-step operation a repository is in the middle of, if any
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RepoState {
+    /// No operation is in progress
+    Clean,
+    Merge,
+    Rebase,
+    /// Applying patches from a mailbox with `git am`
+    Am,
+    CherryPick,
+    Revert,
+    Bisect,
+}
+
+impl RepoState {
+    /// Works out the state from the marker files Git leaves in the Git directory
+    /// while an operation is in progress.
+    pub(crate) fn from_git_dir(git_dir: &Path) -> RepoState {
+        // `git am` uses the same folder as some rebases, but leaves this file in it
+        if git_dir.join("rebase-apply/applying").is_file() {
+            RepoState::Am
+        } else if git_dir.join("rebase-merge").is_dir() || git_dir.join("rebase-apply").is_dir() {
+            RepoState::Rebase
+        } else if git_dir.join("MERGE_HEAD").is_file() {
+            RepoState::Merge
+        } else if git_dir.join("CHERRY_PICK_HEAD").is_file() {
+            RepoState::CherryPick
+        } else if git_dir.join("REVERT_HEAD").is_file() {
+            RepoState::Revert
+        } else if git_dir.join("BISECT_LOG").is_file() {
+            RepoState::Bisect
+        } else {
+            RepoState::Clean
+        }
+    }
+}
@@ -0,0 +1,17 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+use std::path::PathBuf;
+
+/// The result of reverting a commit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RevertOutcome {
+    /// The revert applied cleanly. Holds the SHA of the new commit.
+    Reverted(String),
+    /// The revert applied cleanly, and its changes were left staged instead of committed
+    Staged,
+    /// The revert stopped because of conflicts in these files
+    Conflict(Vec<PathBuf>),
+}
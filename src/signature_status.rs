@@ -0,0 +1,53 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+/// Whether an object's GPG signature could be verified
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureStatus {
+    /// The signature is good, and made with a trusted key
+    Good,
+    /// The signature is good, but the key isn't trusted
+    Untrusted,
+    /// The signature doesn't match the object
+    Bad,
+    /// The signature or the key that made it has expired
+    Expired,
+    /// The key that made the signature has been revoked
+    Revoked,
+    /// The signature can't be checked, usually because the public key isn't available
+    MissingKey,
+    /// The object isn't signed
+    None,
+}
+
+impl SignatureStatus {
+    /// Parses the GPG status lines (`[GNUPG:] <keyword> ...`) that `git verify-tag --raw` and
+    /// `git verify-commit --raw` write to standard error. No status lines means no signature.
+    pub(crate) fn from_gpg_status(output: &str) -> SignatureStatus {
+        let keywords: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.strip_prefix("[GNUPG:] "))
+            .filter_map(|line| line.split_whitespace().next())
+            .collect();
+        let has = |keyword: &str| keywords.contains(&keyword);
+        if has("BADSIG") {
+            SignatureStatus::Bad
+        } else if has("REVKEYSIG") {
+            SignatureStatus::Revoked
+        } else if has("EXPSIG") || has("EXPKEYSIG") {
+            SignatureStatus::Expired
+        } else if has("ERRSIG") || has("NO_PUBKEY") {
+            SignatureStatus::MissingKey
+        } else if has("GOODSIG") {
+            if has("TRUST_UNDEFINED") || has("TRUST_NEVER") {
+                SignatureStatus::Untrusted
+            } else {
+                SignatureStatus::Good
+            }
+        } else {
+            SignatureStatus::None
+        }
+    }
+}
@@ -0,0 +1,16 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+use std::path::PathBuf;
+
+/// The result of applying or popping a stash entry
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StashOutcome {
+    /// The stash applied cleanly
+    Applied,
+    /// The stash was applied with conflicts in these files. A popped stash isn't dropped when
+    /// this happens.
+    Conflict(Vec<PathBuf>),
+}
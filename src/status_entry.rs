@@ -0,0 +1,70 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+use std::path::PathBuf;
+
+/// A changed file, as reported by Git's `--name-status` output
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatusEntry {
+    status: char,
+    similarity: Option<u8>,
+    path: PathBuf,
+    original_path: Option<PathBuf>,
+}
+
+impl StatusEntry {
+    /// The status letter, e.g. `A` (added), `M` (modified), `D` (deleted) or `R` (renamed)
+    pub fn status(&self) -> char {
+        self.status
+    }
+    /// How similar a renamed or copied file is to the original, as a percentage
+    pub fn similarity(&self) -> Option<u8> {
+        self.similarity
+    }
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+    /// The path the file had before it was renamed or copied
+    pub fn original_path(&self) -> &Option<PathBuf> {
+        &self.original_path
+    }
+
+    /// Parses the NUL-delimited output of `--name-status -z`, where each entry is a status
+    /// followed by a path, or by two paths for renames and copies.
+    pub(crate) fn from_name_status_z(output: &str) -> Vec<StatusEntry> {
+        let mut entries = vec![];
+        let mut fields = output.split('\0').filter(|field| !field.is_empty());
+        while let Some(status) = fields.next() {
+            let Some(status_char) = status.chars().next() else {
+                continue;
+            };
+            // Renames and copies have a score after the letter, e.g. R087
+            let similarity = status[status_char.len_utf8()..].parse::<u8>().ok();
+            let Some(path) = fields.next() else {
+                break;
+            };
+            let entry = if status_char == 'R' || status_char == 'C' {
+                match fields.next() {
+                    Some(new_path) => StatusEntry {
+                        status: status_char,
+                        similarity,
+                        path: PathBuf::from(new_path),
+                        original_path: Some(PathBuf::from(path)),
+                    },
+                    None => break,
+                }
+            } else {
+                StatusEntry {
+                    status: status_char,
+                    similarity,
+                    path: PathBuf::from(path),
+                    original_path: None,
+                }
+            };
+            entries.push(entry);
+        }
+        entries
+    }
+}
@@ -0,0 +1,30 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+/// Whether a submodule's checkout matches what the superproject records
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubmoduleState {
+    /// The checked out commit is the recorded one
+    Clean,
+    /// The submodule hasn't been initialized
+    Uninitialized,
+    /// The checked out commit isn't the recorded one
+    OutOfDate,
+    /// The submodule has merge conflicts
+    Conflict,
+}
+
+impl SubmoduleState {
+    /// Returns the state for the prefix character of a line of `git submodule status`
+    pub(crate) fn from_prefix(prefix: char) -> Option<SubmoduleState> {
+        match prefix {
+            ' ' => Some(SubmoduleState::Clean),
+            '-' => Some(SubmoduleState::Uninitialized),
+            '+' => Some(SubmoduleState::OutOfDate),
+            'U' => Some(SubmoduleState::Conflict),
+            _ => None,
+        }
+    }
+}
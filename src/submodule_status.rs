@@ -0,0 +1,51 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+use crate::submodule_state::SubmoduleState;
+use std::path::PathBuf;
+
+/// A submodule, as reported by `git submodule status`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubmoduleStatus {
+    path: PathBuf,
+    sha: String,
+    state: SubmoduleState,
+}
+
+impl SubmoduleStatus {
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+    /// The SHA of the commit checked out in the submodule, or the recorded commit if it isn't
+    /// initialized
+    pub fn sha(&self) -> &str {
+        self.sha.as_str()
+    }
+    pub fn state(&self) -> SubmoduleState {
+        self.state
+    }
+
+    /// Parses the output of `git submodule status`, where each line is a state character,
+    /// the SHA, the path, and, for initialized submodules, a description of the commit in
+    /// parentheses.
+    pub(crate) fn from_submodule_status(output: &str) -> Vec<SubmoduleStatus> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let state = SubmoduleState::from_prefix(line.chars().next()?)?;
+                let (sha, rest) = line[1..].split_once(' ')?;
+                let path = match rest.rsplit_once(" (") {
+                    Some((path, _)) if rest.ends_with(')') => path,
+                    _ => rest,
+                };
+                Some(SubmoduleStatus {
+                    path: PathBuf::from(path),
+                    sha: sha.to_owned(),
+                    state,
+                })
+            })
+            .collect()
+    }
+}
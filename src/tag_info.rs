@@ -0,0 +1,73 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+/// Everything about a tag. The tagger and message are only set for annotated tags.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagInfo {
+    name: String,
+    target_oid: String,
+    tagger_name: Option<String>,
+    tagger_email: Option<String>,
+    tagger_date: Option<String>,
+    message: Option<String>,
+    is_annotated: bool,
+}
+
+impl TagInfo {
+    /// The `--format` that produces the output `from_for_each_ref` parses
+    pub(crate) const FORMAT: &'static str = "--format=%(refname:short)%00%(objecttype)%00\
+        %(objectname)%00%(*objectname)%00%(taggername)%00%(taggeremail:trim)%00\
+        %(taggerdate:iso-strict)%00%(contents)";
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+    /// The OID of the object the tag refers to, usually a commit
+    pub fn target_oid(&self) -> &str {
+        self.target_oid.as_str()
+    }
+    pub fn tagger_name(&self) -> &Option<String> {
+        &self.tagger_name
+    }
+    pub fn tagger_email(&self) -> &Option<String> {
+        &self.tagger_email
+    }
+    /// The date the tag was made, in strict ISO 8601 format
+    pub fn tagger_date(&self) -> &Option<String> {
+        &self.tagger_date
+    }
+    pub fn message(&self) -> &Option<String> {
+        &self.message
+    }
+    pub fn is_annotated(&self) -> bool {
+        self.is_annotated
+    }
+
+    /// Parses the output of `git for-each-ref` for a single tag, run with `TagInfo::FORMAT`
+    pub(crate) fn from_for_each_ref(output: &str) -> Option<TagInfo> {
+        let mut fields = output.splitn(8, '\0');
+        let name = fields.next()?.to_owned();
+        // Only annotated tags are tag objects; lightweight tags point straight at their target
+        let is_annotated = fields.next()? == "tag";
+        let oid = fields.next()?;
+        let peeled_oid = fields.next()?;
+        let annotation = |field: &str| Some(field.to_owned()).filter(|_| is_annotated);
+        let tagger_name = annotation(fields.next()?);
+        let tagger_email = annotation(fields.next()?);
+        let tagger_date = annotation(fields.next()?);
+        // Git adds a newline after the format
+        let contents = fields.next()?;
+        let message = annotation(contents.strip_suffix('\n').unwrap_or(contents));
+        Some(TagInfo {
+            name,
+            target_oid: if is_annotated { peeled_oid } else { oid }.to_owned(),
+            tagger_name,
+            tagger_email,
+            tagger_date,
+            message,
+            is_annotated,
+        })
+    }
+}
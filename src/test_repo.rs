@@ -0,0 +1,165 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+// Not every helper is used by every test
+#![allow(dead_code)]
+
+use crate::git_command::GitCommand;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A scratch directory for tests, optionally initialized as a Git repository.
+/// It's deleted when it goes out of scope.
+pub struct TestRepo {
+    path: PathBuf,
+}
+
+impl TestRepo {
+    /// Creates an empty directory that isn't a Git repository
+    pub fn empty_dir() -> TestRepo {
+        let mut path = std::env::temp_dir();
+        path.push(format!(
+            "git-lib-test-{}-{}",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::SeqCst)
+        ));
+        if path.exists() {
+            std::fs::remove_dir_all(&path).expect("Error removing stale test directory");
+        }
+        std::fs::create_dir_all(&path).expect("Error creating test directory");
+        // Resolve symlinks (e.g. /tmp on macOS) so paths compare equal to Git's output
+        let path = path
+            .canonicalize()
+            .expect("Error canonicalizing test directory");
+        TestRepo { path }
+    }
+
+    /// Creates a repository with no commits, on branch `main`
+    pub fn new() -> TestRepo {
+        let repo = TestRepo::empty_dir();
+        repo.git(&["init", "-q", "-b", "main"]);
+        repo.configure_identity();
+        repo
+    }
+
+    /// Creates a repository where `main` and `feature` make conflicting changes to `file.txt`.
+    /// `main` is checked out.
+    pub fn with_conflicting_branch() -> TestRepo {
+        let repo = TestRepo::new();
+        repo.commit_file("file.txt", "base\n", "Base commit");
+        repo.git(&["checkout", "-q", "-b", "feature"]);
+        repo.commit_file("file.txt", "feature\n", "Feature commit");
+        repo.git(&["checkout", "-q", "main"]);
+        repo.commit_file("file.txt", "main\n", "Main commit");
+        repo
+    }
+
+    /// Creates a bare repository, on branch `main`
+    pub fn bare() -> TestRepo {
+        let repo = TestRepo::empty_dir();
+        repo.git(&["init", "-q", "--bare", "-b", "main"]);
+        repo
+    }
+
+    /// Creates a clone of another repository
+    pub fn clone_of(origin: &TestRepo) -> TestRepo {
+        let repo = TestRepo::empty_dir();
+        repo.git(&["clone", "-q", origin.path_str(), "."]);
+        repo.configure_identity();
+        repo
+    }
+
+    fn configure_identity(&self) {
+        self.git(&["config", "user.name", "Test User"]);
+        self.git(&["config", "user.email", "test@example.com"]);
+        self.git(&["config", "commit.gpgsign", "false"]);
+        self.git(&["config", "tag.gpgsign", "false"]);
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn path_str(&self) -> &str {
+        self.path.to_str().expect("Test path isn't valid UTF-8")
+    }
+
+    /// Runs a Git command in the directory and returns its output, panicking if it fails
+    pub fn git(&self, args: &[&str]) -> String {
+        let (command, args) = args.split_first().expect("No Git command");
+        match GitCommand::git_command::<String>(
+            command,
+            Some(args.to_vec()),
+            None,
+            Some(&self.path),
+        ) {
+            Ok(output) => output,
+            Err(error) => panic!("git {} {}: {}", command, args.join(" "), error),
+        }
+    }
+
+    /// Runs a Git command that is expected to fail, e.g. a conflicting merge
+    pub fn git_fails(&self, args: &[&str]) {
+        let (command, args) = args.split_first().expect("No Git command");
+        let output = GitCommand::run_git_command::<String>(
+            command,
+            Some(args.to_vec()),
+            None,
+            Some(&self.path),
+        )
+        .expect("Error running git");
+        assert!(
+            !output.status.success(),
+            "git {} {} unexpectedly succeeded",
+            command,
+            args.join(" ")
+        );
+    }
+
+    /// Writes a file relative to the directory, creating parent directories as needed
+    pub fn write(&self, file: &str, contents: &str) {
+        let path = self.path.join(file);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("Error creating parent directory");
+        }
+        std::fs::write(path, contents).expect("Error writing test file");
+    }
+
+    /// Writes a file, stages it and commits it, returning the new commit's SHA
+    pub fn commit_file(&self, file: &str, contents: &str, message: &str) -> String {
+        self.write(file, contents);
+        self.git(&["add", "--", file]);
+        self.git(&["commit", "-q", "-m", message]);
+        self.head()
+    }
+
+    /// Adds another test repository as a submodule at a path and commits it
+    pub fn add_submodule(&self, submodule: &TestRepo, path: &str) {
+        // Git doesn't clone submodules from local paths unless it's told it's safe
+        self.git(&[
+            "-c",
+            "protocol.file.allow=always",
+            "submodule",
+            "add",
+            "-q",
+            submodule.path_str(),
+            path,
+        ]);
+        self.git(&["commit", "-q", "-m", "Add submodule"]);
+    }
+
+    /// Returns the SHA of HEAD
+    pub fn head(&self) -> String {
+        self.git(&["rev-parse", "HEAD"]).trim_end().to_owned()
+    }
+}
+
+impl Drop for TestRepo {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,71 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+use crate::object_type::ObjectType;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// An entry in a tree object, as reported by `git ls-tree`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeEntry {
+    mode: String,
+    kind: ObjectType,
+    oid: String,
+    path: PathBuf,
+}
+
+impl TreeEntry {
+    pub fn new<T: Into<PathBuf>>(mode: &str, kind: ObjectType, oid: &str, path: T) -> TreeEntry {
+        TreeEntry {
+            mode: mode.to_owned(),
+            kind,
+            oid: oid.to_owned(),
+            path: path.into(),
+        }
+    }
+
+    /// The file mode, e.g. `100644`, `100755` (executable), `120000` (symlink) or `040000` (tree)
+    pub fn mode(&self) -> &str {
+        self.mode.as_str()
+    }
+    pub fn kind(&self) -> ObjectType {
+        self.kind
+    }
+    pub fn oid(&self) -> &str {
+        self.oid.as_str()
+    }
+    /// The path, relative to the tree that was listed
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Whether the mode is one Git allows in a tree, for this kind of entry
+    pub(crate) fn has_valid_mode(&self) -> bool {
+        match self.kind {
+            ObjectType::Blob => matches!(self.mode(), "100644" | "100755" | "120000"),
+            ObjectType::Tree => matches!(self.mode(), "040000" | "40000"),
+            ObjectType::Commit => self.mode() == "160000",
+            ObjectType::Tag => false,
+        }
+    }
+
+    /// Parses the output of `git ls-tree -z`, where each entry is
+    /// `<mode> SP <type> SP <oid> TAB <path>` terminated by a NUL.
+    pub(crate) fn from_ls_tree_z(output: &str) -> Vec<TreeEntry> {
+        output
+            .split('\0')
+            .filter_map(|record| {
+                let (info, path) = record.split_once('\t')?;
+                let mut info = info.split(' ');
+                Some(TreeEntry {
+                    mode: info.next()?.to_owned(),
+                    kind: ObjectType::from_str(info.next()?).ok()?,
+                    oid: info.next()?.to_owned(),
+                    path: PathBuf::from(path),
+                })
+            })
+            .collect()
+    }
+}
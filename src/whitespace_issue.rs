@@ -0,0 +1,45 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+use std::path::PathBuf;
+
+/// A whitespace error or leftover conflict marker reported by `git diff --check`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WhitespaceIssue {
+    file: PathBuf,
+    line: u32,
+    message: String,
+}
+
+impl WhitespaceIssue {
+    pub fn file(&self) -> &PathBuf {
+        &self.file
+    }
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+    /// Git's description of the problem, e.g. `trailing whitespace.`
+    pub fn message(&self) -> &str {
+        self.message.as_str()
+    }
+
+    /// Parses the output of `git diff --check`, where each issue is reported as
+    /// `<file>:<line>: <message>`, followed by the offending line prefixed with `+`.
+    pub(crate) fn from_diff_check(output: &str) -> Vec<WhitespaceIssue> {
+        output
+            .lines()
+            .filter(|line| !line.starts_with('+'))
+            .filter_map(|line| {
+                let (location, message) = line.split_once(": ")?;
+                let (file, line) = location.rsplit_once(':')?;
+                Some(WhitespaceIssue {
+                    file: PathBuf::from(file),
+                    line: line.parse().ok()?,
+                    message: message.to_owned(),
+                })
+            })
+            .collect()
+    }
+}
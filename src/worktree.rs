@@ -0,0 +1,95 @@
+/*
+ * Copyright (c) 2026 Paul Sobolik
+ * Created 2026-10-16
+ */
+
+use std::path::PathBuf;
+
+/// A working tree attached to a repository, as reported by `git worktree list`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Worktree {
+    path: PathBuf,
+    head: Option<String>,
+    branch: Option<String>,
+    bare: bool,
+    detached: bool,
+    locked: bool,
+    lock_reason: Option<String>,
+    prunable: bool,
+}
+
+impl Worktree {
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+    /// The SHA of the commit that is checked out
+    pub fn head(&self) -> &Option<String> {
+        &self.head
+    }
+    /// The full name of the branch that is checked out, e.g. `refs/heads/main`
+    pub fn branch(&self) -> &Option<String> {
+        &self.branch
+    }
+    pub fn bare(&self) -> bool {
+        self.bare
+    }
+    pub fn detached(&self) -> bool {
+        self.detached
+    }
+    pub fn locked(&self) -> bool {
+        self.locked
+    }
+    pub fn lock_reason(&self) -> &Option<String> {
+        &self.lock_reason
+    }
+    /// Whether the worktree can be pruned, e.g. because its directory is gone
+    pub fn prunable(&self) -> bool {
+        self.prunable
+    }
+
+    /// Parses the output of `git worktree list --porcelain -z`, where each attribute is
+    /// terminated by a NUL, and each worktree by an additional NUL.
+    pub(crate) fn from_porcelain_z(output: &str) -> Vec<Worktree> {
+        output
+            .split("\0\0")
+            .filter_map(|record| {
+                let mut worktree: Option<Worktree> = None;
+                for attribute in record.split('\0') {
+                    let (name, value) = match attribute.split_once(' ') {
+                        Some((name, value)) => (name, Some(value)),
+                        None => (attribute, None),
+                    };
+                    if name == "worktree" {
+                        worktree = Some(Worktree {
+                            path: PathBuf::from(value.unwrap_or_default()),
+                            head: None,
+                            branch: None,
+                            bare: false,
+                            detached: false,
+                            locked: false,
+                            lock_reason: None,
+                            prunable: false,
+                        });
+                        continue;
+                    }
+                    let Some(worktree) = worktree.as_mut() else {
+                        continue;
+                    };
+                    match name {
+                        "HEAD" => worktree.head = value.map(|value| value.to_owned()),
+                        "branch" => worktree.branch = value.map(|value| value.to_owned()),
+                        "bare" => worktree.bare = true,
+                        "detached" => worktree.detached = true,
+                        "locked" => {
+                            worktree.locked = true;
+                            worktree.lock_reason = value.map(|value| value.to_owned());
+                        }
+                        "prunable" => worktree.prunable = true,
+                        _ => {}
+                    }
+                }
+                worktree
+            })
+            .collect()
+    }
+}